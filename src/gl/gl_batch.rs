@@ -0,0 +1,126 @@
+/*
+Copyright 2021 Gonzalo Fernández Hernández
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use crate::{ImageId, FontId};
+
+use glium::index::{IndexBuffer, IndexBufferSlice, PrimitiveType};
+use glium::vertex::{Vertex, VertexBuffer, VertexBufferSlice};
+
+/// Number of vertices and indices the buffers of a batch have room for at least
+const MIN_BUFFER_LEN: usize = 1024;
+
+#[derive(Copy, Clone)]
+pub struct ColorVertex {
+    pub position: [f32; 2],
+    pub color: [f32; 4],
+}
+
+implement_vertex!(ColorVertex, position, color);
+
+/// A vertex of a texture. The color of the texture is multiplied by the color of the vertex.
+#[derive(Copy, Clone)]
+pub struct TextureVertex {
+    pub position: [f32; 2],
+    pub tex_coords: [f32; 2],
+    pub color: [f32; 4],
+}
+
+implement_vertex!(TextureVertex, position, tex_coords, color);
+
+/// What the triangles of a batch are drawn with
+///     * Shapes: The color program, with a color for every vertex
+///     * Image: The texture program, with the texture of an image
+///     * Text: The texture program, with the texture of the glyphs of a font
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum BatchKind {
+    Shapes,
+    Image(ImageId),
+    Text(FontId),
+}
+
+/// Triangles waiting to be drawn together in a single draw call
+pub struct Batch<V: Copy> {
+    pub vertices: Vec<V>,
+    pub indices: Vec<u32>,
+}
+
+impl<V: Copy> Batch<V> {
+    pub fn new() -> Self {
+        Batch{vertices: Vec::new(), indices: Vec::new()}
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Removes the triangles, keeping the memory for the next ones
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.indices.clear();
+    }
+
+    /// Adds a convex polygon as a fan of triangles from its first vertex
+    pub fn add_fan(&mut self, vertices: &[V]) {
+        if vertices.len() < 3 {
+            return;
+        }
+        let first = self.vertices.len() as u32;
+        self.vertices.extend_from_slice(vertices);
+        for i in 1..(vertices.len() as u32 - 1) {
+            self.indices.extend_from_slice(&[first, first + i, first + i + 1]);
+        }
+    }
+}
+
+/// GPU buffers the batches are copied into before they are drawn.
+/// They are kept between flushes and frames, and only replaced by bigger ones when a batch does not fit.
+pub struct BatchBuffers<V: Vertex> {
+    vertices: Option<VertexBuffer<V>>,
+    indices: Option<IndexBuffer<u32>>,
+}
+
+impl<V: Vertex> BatchBuffers<V> {
+    pub fn new() -> Self {
+        BatchBuffers{vertices: None, indices: None}
+    }
+
+    /// Copies the triangles of a batch into the buffers, growing them if they are too small
+    pub fn upload(&mut self, display: &glium::Display, batch: &Batch<V>) {
+        let (vertex_len, index_len) = (batch.vertices.len(), batch.indices.len());
+        if self.vertices.as_ref().is_none_or(|x| x.len() < vertex_len) {
+            let len = vertex_len.next_power_of_two().max(MIN_BUFFER_LEN);
+            self.vertices = Some(VertexBuffer::empty_dynamic(display, len).unwrap());
+        }
+        if self.indices.as_ref().is_none_or(|x| x.len() < index_len) {
+            let len = index_len.next_power_of_two().max(MIN_BUFFER_LEN);
+            self.indices = Some(IndexBuffer::empty_dynamic(display, PrimitiveType::TrianglesList, len).unwrap());
+        }
+        if let (Some(vertices), Some(indices)) = (&mut self.vertices, &mut self.indices) {
+            // The previous content may still be in use by a draw call, so the driver can give new memory
+            vertices.invalidate();
+            indices.invalidate();
+            vertices.slice_mut(0..vertex_len).unwrap().write(&batch.vertices);
+            indices.slice_mut(0..index_len).unwrap().write(&batch.indices);
+        }
+    }
+
+    /// Parts of the buffers that hold the triangles of a batch uploaded
+    pub fn slices(&self, batch: &Batch<V>) -> Option<(VertexBufferSlice<'_, V>, IndexBufferSlice<'_, u32>)> {
+        let vertices = self.vertices.as_ref()?.slice(0..batch.vertices.len())?;
+        let indices = self.indices.as_ref()?.slice(0..batch.indices.len())?;
+        Some((vertices, indices))
+    }
+}
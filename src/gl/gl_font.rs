@@ -0,0 +1,242 @@
+/*
+Copyright 2021 Gonzalo Fernández Hernández
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use crate::{default_font_bytes, FontId, FontWeight, TextStyle, TextMetrics, FontError, FontProvider};
+use crate::gl::gl_image::GLImage;
+
+use std::collections::HashMap;
+
+use rusttype::{Font, Scale, point};
+
+/// Size in pixels the glyphs are rasterized at. Text of other sizes is scaled from it.
+const RASTER_SIZE: u32 = 48;
+
+/// Width in pixels of the texture with the glyphs of a font
+const TEXTURE_WIDTH: u32 = 1024;
+
+/// Character drawn in place of the ones that are not in the texture
+const REPLACEMENT: char = '\u{FFFD}';
+
+/// Empty pixels around every glyph in the texture, so they do not bleed into each other when scaled
+const GLYPH_MARGIN: u32 = 2;
+
+/// Where a glyph is in the texture of its font, and where it is drawn. The sizes are in units of the font size.
+///     * tex: Left, top, right and bottom of the glyph in the texture
+///     * left, top: Position of the top left corner of the glyph from the pen on the baseline (up is positive)
+///     * width, height: Size of the glyph
+///     * advance: Distance from the pen to the next one
+pub struct GlyphInfo {
+    pub tex: (f32, f32, f32, f32),
+    pub left: f32,
+    pub top: f32,
+    pub width: f32,
+    pub height: f32,
+    pub advance: f32,
+}
+
+/// A font rasterized into a texture, ready to draw text with OpenGL.
+/// The texture is white, with the coverage of the glyphs in the alpha, so it is tinted with the color of the text.
+pub struct GLFont {
+    texture: GLImage,
+    /// Distance from the top of the line to the baseline, in units of the font size
+    ascent: f32,
+    /// Distance from the baseline to the bottom of the line, in units of the font size (negative)
+    descent: f32,
+    glyphs: HashMap<char, GlyphInfo>,
+}
+
+impl GLFont {
+    /// Rasterizes a font from the bytes of a TTF/OTF file
+    pub fn new(bytes: &[u8], display: &glium::Display) -> Option<Self> {
+        let font = Font::from_bytes(bytes.to_vec()).ok()?;
+        let v_metrics = font.v_metrics(Scale::uniform(RASTER_SIZE as f32));
+        let (pixels, height, glyphs) = GLFont::rasterize(&font);
+        let texture = GLImage::new(display, TEXTURE_WIDTH, height, pixels).ok()?;
+        Some(GLFont{
+            texture,
+            ascent: v_metrics.ascent / RASTER_SIZE as f32,
+            descent: v_metrics.descent / RASTER_SIZE as f32,
+            glyphs})
+    }
+
+    /// Draws the glyphs of the characters in rows, from the top left corner of a texture TEXTURE_WIDTH wide
+    /// returns:
+    ///     The RGBA pixels of the texture, its height, and where every glyph is
+    fn rasterize(font: &Font) -> (Vec<u8>, u32, HashMap<char, GlyphInfo>) {
+        let scale = Scale::uniform(RASTER_SIZE as f32);
+        let size = RASTER_SIZE as f32;
+        let glyphs: Vec<_> = GLFont::character_list().into_iter()
+            .map(|c| (c, font.glyph(c).scaled(scale).positioned(point(0_f32, 0_f32))))
+            .collect();
+        let mut places = Vec::new();
+        let (mut x, mut y, mut row_height) = (GLYPH_MARGIN, GLYPH_MARGIN, 0);
+        for (_, glyph) in glyphs.iter() {
+            let (width, height) = glyph.pixel_bounding_box().map(|x| (x.width() as u32, x.height() as u32)).unwrap_or((0, 0));
+            if x + width + GLYPH_MARGIN > TEXTURE_WIDTH {
+                x = GLYPH_MARGIN;
+                y += row_height + GLYPH_MARGIN;
+                row_height = 0;
+            }
+            places.push((x, y));
+            x += width + GLYPH_MARGIN;
+            row_height = row_height.max(height);
+        }
+        let texture_height = y + row_height + GLYPH_MARGIN;
+
+        let mut pixels = vec![0_u8; (TEXTURE_WIDTH * texture_height * 4) as usize];
+        pixels.chunks_mut(4).for_each(|x| x[..3].copy_from_slice(&[255, 255, 255]));
+        let mut infos = HashMap::new();
+        for ((character, glyph), (px, py)) in glyphs.iter().zip(places) {
+            let advance = glyph.unpositioned().h_metrics().advance_width / size;
+            let bounds = match glyph.pixel_bounding_box() {
+                Some(bounds) => bounds,
+                None => {
+                    infos.insert(*character, GlyphInfo{tex: (0_f32, 0_f32, 0_f32, 0_f32), left: 0_f32, top: 0_f32, width: 0_f32, height: 0_f32, advance});
+                    continue;
+                },
+            };
+            glyph.draw(|gx, gy, value| {
+                let index = (((py + gy) * TEXTURE_WIDTH + px + gx) * 4 + 3) as usize;
+                pixels[index] = (value * 255_f32).round() as u8;
+            });
+            let (width, height) = (bounds.width() as u32, bounds.height() as u32);
+            let (tw, th) = (TEXTURE_WIDTH as f32, texture_height as f32);
+            infos.insert(*character, GlyphInfo{
+                // The texture is stored upside down
+                tex: (px as f32 / tw, 1_f32 - py as f32 / th, (px + width) as f32 / tw, 1_f32 - (py + height) as f32 / th),
+                left: bounds.min.x as f32 / size,
+                top: -bounds.min.y as f32 / size,
+                width: width as f32 / size,
+                height: height as f32 / size,
+                advance,
+            });
+        }
+        (pixels, texture_height, infos)
+    }
+
+    /// Characters rasterized in the texture: ASCII, Latin-1, '…' and the replacement character
+    fn character_list() -> Vec<char> {
+        let mut characters: Vec<char> = (0x20_u8..0x7f_u8).chain(0xa0_u8..=0xff_u8).map(char::from).collect();
+        characters.extend_from_slice(&['\u{2026}', '\u{20ac}', REPLACEMENT]);
+        characters
+    }
+
+    pub fn ascent(&self) -> f32 {
+        self.ascent
+    }
+
+    pub fn texture(&self) -> &glium::texture::Texture2d {
+        &self.texture.texture
+    }
+
+    /// Position of a character in the texture
+    pub fn glyph(&self, character: char) -> Option<&GlyphInfo> {
+        GLFont::find_glyph(&self.glyphs, character)
+    }
+
+    /// The glyph of a character, or of the replacement character if it is not in the texture,
+    /// so the text measured is the text drawn
+    fn find_glyph(glyphs: &HashMap<char, GlyphInfo>, character: char) -> Option<&GlyphInfo> {
+        glyphs.get(&character).or_else(|| glyphs.get(&REPLACEMENT))
+    }
+}
+
+/// Fonts loaded in a window. They live as long as the window, so they are rasterized only once.
+pub struct GLFonts {
+    display: glium::Display,
+    fonts: Vec<GLFont>,
+}
+
+impl GLFonts {
+    /// Creates the store with the default font of the system, if any, as font 0
+    pub fn new(display: &glium::Display) -> Self {
+        let fonts = default_font_bytes().and_then(|bytes| GLFont::new(&bytes, display)).into_iter().collect();
+        GLFonts{display: display.clone(), fonts}
+    }
+
+    pub fn get(&self, font: FontId) -> Option<&GLFont> {
+        self.fonts.get(font)
+    }
+}
+
+impl FontProvider for GLFonts {
+
+    fn load_font_from_bytes(&mut self, bytes: Vec<u8>) -> Result<FontId, FontError> {
+        let font = GLFont::new(&bytes, &self.display).ok_or(FontError::InvalidFont)?;
+        self.fonts.push(font);
+        Ok(self.fonts.len() - 1)
+    }
+
+    fn measure_text(&self, text: &str, style: &TextStyle) -> TextMetrics {
+        let font = match self.fonts.get(style.font) {
+            Some(font) => font,
+            None => return TextMetrics::default(),
+        };
+        let mut width = text.chars().filter_map(|x| font.glyph(x)).map(|x| x.advance).sum::<f32>() * style.size;
+        if style.weight == FontWeight::Bold && width > 0_f32 {
+            width += 1_f32;
+        }
+        TextMetrics{
+            width,
+            height: (font.ascent - font.descent) * style.size,
+            baseline: font.ascent * style.size}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glyphs_are_drawn_where_the_texture_coordinates_say() {
+        let font = match default_font_bytes().and_then(|bytes| Font::from_bytes(bytes).ok()) {
+            Some(font) => font,
+            None => return,
+        };
+        let (pixels, height, glyphs) = GLFont::rasterize(&font);
+        assert_eq!(pixels.len(), (TEXTURE_WIDTH * height * 4) as usize);
+        let alpha = |u: f32, v: f32| {
+            let (x, y) = ((u * TEXTURE_WIDTH as f32) as u32, ((1_f32 - v) * height as f32) as u32);
+            pixels[((y * TEXTURE_WIDTH + x) * 4 + 3) as usize]
+        };
+
+        let glyph = glyphs.get(&'H').unwrap();
+        let (u1, v1, u2, v2) = glyph.tex;
+        assert!(u1 < u2 && v1 > v2);
+        // The left stem of the H is inside its glyph, and nothing is drawn just outside it
+        assert!(alpha(u1 + (u2 - u1) * 0.1_f32, (v1 + v2) * 0.5_f32) > 0);
+        assert_eq!(alpha(u1 - 1_f32 / TEXTURE_WIDTH as f32, (v1 + v2) * 0.5_f32), 0);
+        assert!(glyph.width > 0_f32 && glyph.top > 0_f32 && glyph.advance >= glyph.width);
+
+        let space = glyphs.get(&' ').unwrap();
+        assert!(space.width == 0_f32 && space.advance > 0_f32);
+    }
+
+    #[test]
+    fn characters_out_of_the_texture_use_the_replacement() {
+        let font = match default_font_bytes().and_then(|bytes| Font::from_bytes(bytes).ok()) {
+            Some(font) => font,
+            None => return,
+        };
+        let (_, _, glyphs) = GLFont::rasterize(&font);
+        let accented = GLFont::find_glyph(&glyphs, '\u{e9}').unwrap();
+        assert!(accented.width > 0_f32 && accented.advance > 0_f32);
+        let replacement = glyphs.get(&REPLACEMENT).unwrap();
+        let missing = GLFont::find_glyph(&glyphs, '\u{4e2d}').unwrap();
+        assert!(missing.advance > 0_f32);
+        assert_eq!(missing.tex, replacement.tex);
+    }
+}
@@ -14,20 +14,21 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
-extern crate image;
-
-use std::io::Cursor;
+use crate::ImageError;
 
 pub struct GLImage {
     pub texture: glium::texture::Texture2d,
 }
 
 impl GLImage {
-    pub fn new(location: &str, display: & glium::Display) -> Self {
-        let img = image::load(Cursor::new(location), image::ImageFormat::Png).unwrap().to_rgba8();
-        let img_size = img.dimensions();
-        let img_glium = glium::texture::RawImage2d::from_raw_rgba_reversed(&img, img_size);
-        let texture = glium::texture::Texture2d::new(display as &glium::backend::Facade, img_glium).unwrap();
-        GLImage{texture} 
+    /// Creates a texture from pixels in rows from the top, 4 bytes (RGBA) each
+    pub fn new(display: &glium::Display, width: u32, height: u32, pixels: Vec<u8>) -> Result<Self, ImageError> {
+        if pixels.len() != width as usize * height as usize * 4 {
+            return Err(ImageError::InvalidSize);
+        }
+        let img_glium = glium::texture::RawImage2d::from_raw_rgba_reversed(&pixels, (width, height));
+        let texture = glium::texture::Texture2d::new(display, img_glium)
+            .map_err(|error| ImageError::Texture(error.to_string()))?;
+        Ok(GLImage{texture})
     }
-}
\ No newline at end of file
+}
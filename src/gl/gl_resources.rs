@@ -0,0 +1,177 @@
+/*
+Copyright 2021 Gonzalo Fernández Hernández
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use crate::{ImageId, ImageError, ImageProvider};
+use crate::{FontId, TextStyle, TextMetrics, FontError, FontProvider};
+use crate::gl::gl_image::GLImage;
+use crate::gl::gl_font::GLFonts;
+use crate::gl::gl_batch::{Batch, BatchBuffers, BatchKind, ColorVertex, TextureVertex};
+
+/// GPU resources of a window: shader programs, fonts, textures and the buffers of the batches.
+/// They live as long as the window, so the ids of the fonts and images loaded stay valid in every frame.
+pub struct GLResources {
+    display: glium::Display,
+    program: glium::Program,
+    tex_program: glium::Program,
+    fonts: GLFonts,
+    images: Vec<GLImage>,
+    shape_buffers: BatchBuffers<ColorVertex>,
+    image_buffers: BatchBuffers<TextureVertex>,
+}
+
+impl GLResources {
+    /// Compiles the shader programs and loads the default font
+    pub fn new(display: &glium::Display) -> Self {
+        let vertex_shader_src = r#"
+        #version 140
+
+        in vec2 position;
+        in vec4 color;
+        out vec4 v_color;
+
+        void main() {
+            gl_Position = vec4(position, 0.0, 1.0);
+            v_color = color;
+        }
+        "#;
+        let fragment_shader_src = r#"
+            #version 140
+
+            in vec4 v_color;
+
+            void main() {
+                gl_FragColor = v_color;
+            }
+        "#;
+        let program = glium::Program::from_source(display, vertex_shader_src, fragment_shader_src, None).unwrap();
+
+        let vertex_shader_src = r#"
+        #version 140
+
+        in vec2 position;
+        in vec2  tex_coords;
+        in vec4 color;
+        out vec2 v_tex_coords;
+        out vec4 v_tint;
+
+        uniform mat4 matrix;
+
+        void main() {
+            v_tex_coords = tex_coords;
+            v_tint = color;
+            gl_Position = matrix * vec4(position, 0.0, 1.0);
+        }
+        "#;
+
+        let fragment_shader_src = r#"
+            #version 140
+
+            in vec2 v_tex_coords;
+            in vec4 v_tint;
+            out vec4 v_color;
+
+            uniform sampler2D tex;
+
+            void main() {
+                v_color = texture(tex, v_tex_coords) * v_tint;
+            }
+        "#;
+        let tex_program = glium::Program::from_source(display, vertex_shader_src, fragment_shader_src, None).unwrap();
+
+        GLResources{
+            display: display.clone(),
+            program,
+            tex_program,
+            fonts: GLFonts::new(display),
+            images: Vec::new(),
+            shape_buffers: BatchBuffers::new(),
+            image_buffers: BatchBuffers::new(),
+        }
+    }
+
+    pub fn display(&self) -> &glium::Display {
+        &self.display
+    }
+
+    /// Program that draws shapes with a color for every vertex
+    pub fn program(&self) -> &glium::Program {
+        &self.program
+    }
+
+    /// Program that draws textures
+    pub fn tex_program(&self) -> &glium::Program {
+        &self.tex_program
+    }
+
+    pub fn fonts(&self) -> &GLFonts {
+        &self.fonts
+    }
+
+    pub fn image(&self, img: ImageId) -> Option<&GLImage> {
+        self.images.get(img)
+    }
+
+    /// Texture a batch is drawn with, if it uses one
+    pub fn texture(&self, kind: BatchKind) -> Option<&glium::texture::Texture2d> {
+        match kind {
+            BatchKind::Image(img) => self.images.get(img).map(|x| &x.texture),
+            BatchKind::Text(font) => self.fonts.get(font).map(|x| x.texture()),
+            BatchKind::Shapes => None,
+        }
+    }
+
+    /// Copies a batch of shapes into the buffers drawn with the color program
+    pub fn upload_shapes(&mut self, batch: &Batch<ColorVertex>) {
+        self.shape_buffers.upload(&self.display, batch);
+    }
+
+    pub fn shape_buffers(&self) -> &BatchBuffers<ColorVertex> {
+        &self.shape_buffers
+    }
+
+    /// Copies a batch of image or text triangles into the buffers drawn with the texture program
+    pub fn upload_images(&mut self, batch: &Batch<TextureVertex>) {
+        self.image_buffers.upload(&self.display, batch);
+    }
+
+    pub fn image_buffers(&self) -> &BatchBuffers<TextureVertex> {
+        &self.image_buffers
+    }
+}
+
+impl ImageProvider for GLResources {
+
+    fn load_image_from_rgba(&mut self, width: u32, height: u32, pixels: Vec<u8>) -> Result<ImageId, ImageError> {
+        let image = GLImage::new(&self.display, width, height, pixels)?;
+        self.images.push(image);
+        Ok(self.images.len() - 1)
+    }
+
+    fn image_size(&self, img: ImageId) -> Option<(u32, u32)> {
+        self.images.get(img).map(|x| x.texture.dimensions())
+    }
+}
+
+impl FontProvider for GLResources {
+
+    fn load_font_from_bytes(&mut self, bytes: Vec<u8>) -> Result<FontId, FontError> {
+        self.fonts.load_font_from_bytes(bytes)
+    }
+
+    fn measure_text(&self, text: &str, style: &TextStyle) -> TextMetrics {
+        self.fonts.measure_text(text, style)
+    }
+}
@@ -16,148 +16,239 @@ limitations under the License.
 
 use crate::Color;
 use crate::Surface;
-use crate::ImageId;
-use crate::gl::gl_image::GLImage;
+use crate::{ImageId, ImageError, ImageProvider};
+use crate::TransformStack;
+use crate::gl::gl_resources::GLResources;
+use crate::{FontId, FontWeight, TextStyle, TextMetrics, FontError, FontProvider};
+
+use crate::gl::gl_batch::{Batch, BatchKind, ColorVertex, TextureVertex};
 
 use glium::Surface as RawGlSurface;
 use std::cmp;
 
-#[derive(Copy, Clone)]
-struct Vertex {
-    position: [f32; 2],
+/// Left, top, right and bottom of a rectangle
+type Quad = (f32, f32, f32, f32);
+
+/// A frame of a GLWindow. The fonts and images are kept in the resources of the window.
+/// The shapes, images and text drawn are added to batches of triangles, that are drawn in a single call
+/// when something drawn with another program or texture comes, or when the frame ends.
+pub struct GLSurface <'a> {
+    resources: &'a mut GLResources,
+    raw_surface: Option<glium::Frame>,
+    dimensions: (u32, u32),
+    kind: BatchKind,
+    shapes: Batch<ColorVertex>,
+    images: Batch<TextureVertex>,
+    /// Opacities pushed, each one already multiplied by the ones below
+    opacity: Vec<f32>,
+    /// Scissor rectangles pushed, each one already intersected with the ones below
+    clip: Vec<glium::Rect>,
+    transform: TransformStack,
 }
 
-implement_vertex!(Vertex, position);
+impl<'a> GLSurface<'a>  {
+    pub fn new(resources: &'a mut GLResources) -> GLSurface<'a> {
+        let raw_surface = resources.display().draw();
+        let dimensions = raw_surface.get_dimensions();
+        GLSurface{
+            resources,
+            raw_surface: Some(raw_surface),
+            dimensions,
+            kind: BatchKind::Shapes,
+            shapes: Batch::new(),
+            images: Batch::new(),
+            opacity: Vec::new(),
+            clip: Vec::new(),
+            transform: TransformStack::new(),
+        }
+    }
 
-#[derive(Copy, Clone)]
-struct ColorVertex {
-    color: [f32; 4],
-}
+    /// Maps a rectangle to a glium rectangle in pixels (its origin is the bottom left corner)
+    fn to_pixel_rect(&self, x1: f32, y1: f32, x2: f32, y2: f32) -> glium::Rect {
+        let left = ((x1.min(x2) + 1_f32) * 0.5_f32 * self.dimensions.0 as f32).max(0_f32);
+        let bottom = ((y1.min(y2) + 1_f32) * 0.5_f32 * self.dimensions.1 as f32).max(0_f32);
+        let right = ((x1.max(x2) + 1_f32) * 0.5_f32 * self.dimensions.0 as f32).max(0_f32);
+        let top = ((y1.max(y2) + 1_f32) * 0.5_f32 * self.dimensions.1 as f32).max(0_f32);
+        glium::Rect{left: left as u32, bottom: bottom as u32, width: (right - left) as u32, height: (top - bottom) as u32}
+    }
 
-implement_vertex!(ColorVertex, color);
+    fn to_color_vertex(x: f32, y: f32, color: &Color) -> ColorVertex {
+        ColorVertex{position: [x, y], color: [color.r, color.g, color.b, color.a]}
+    }
 
-#[derive(Copy, Clone)]
-struct TextureVertex {
-    position: [f32; 2],
-    tex_coords: [f32; 2],
-}
+    fn to_tex_vertex(x: f32, y: f32, tx: f32, ty: f32, color: [f32; 4]) -> TextureVertex {
+        TextureVertex{position: [x, y], tex_coords: [tx, ty], color}
+    }
 
-implement_vertex!(TextureVertex, position, tex_coords);
+    /// Part of a rectangle in pixels inside the clipping pushed
+    fn clip_rect(&self, rect: glium::Rect) -> glium::Rect {
+        match self.clip.last() {
+            Some(clip) => {
+                let left = rect.left.max(clip.left);
+                let bottom = rect.bottom.max(clip.bottom);
+                let right = (rect.left + rect.width).min(clip.left + clip.width).max(left);
+                let top = (rect.bottom + rect.height).min(clip.bottom + clip.height).max(bottom);
+                glium::Rect{left, bottom, width: right - left, height: top - bottom}
+            },
+            None => rect,
+        }
+    }
 
+    /// Opacity everything is drawn with, from the opacities pushed
+    fn opacity(&self) -> f32 {
+        self.opacity.last().copied().unwrap_or(1_f32)
+    }
 
-pub struct GLSurface <'a, 'b> {
-    display: &'a glium::Display,
-    raw_surface: Option<glium::Frame>,
-    program: &'b glium::Program,
-    tex_program: &'b glium::Program,
-    dimensions: (u32, u32),
-    textures: Vec<GLImage>
-}
+    /// Draws the pending batch if the next geometry is drawn with something else
+    fn use_batch(&mut self, kind: BatchKind) {
+        if self.kind != kind {
+            self.flush();
+            self.kind = kind;
+        }
+    }
 
-impl<'a, 'b> GLSurface<'a, 'b>  {
-    pub fn new(display: &'a glium::Display, program: &'b glium::Program, tex_program: &'b glium::Program) -> GLSurface<'a, 'b> {
-        let raw_surface = display.draw();
-        let dimensions = raw_surface.get_dimensions();
-        let textures = Vec::new();
-        GLSurface{ display, raw_surface: Some(raw_surface), program, tex_program, dimensions, textures }
+    /// Draws the triangles of the pending batch in a single call
+    fn flush(&mut self) {
+        let surf = match &mut self.raw_surface {
+            Some(surf) => surf,
+            None => return,
+        };
+        let params = glium::DrawParameters {
+            blend: glium::Blend::alpha_blending(),
+            scissor: self.clip.last().copied(),
+            .. Default::default()
+        };
+        match self.kind {
+            BatchKind::Shapes if !self.shapes.is_empty() => {
+                self.resources.upload_shapes(&self.shapes);
+                if let Some((vertices, indices)) = self.resources.shape_buffers().slices(&self.shapes) {
+                    surf.draw(vertices, indices, self.resources.program(), &glium::uniforms::EmptyUniforms, &params).unwrap();
+                }
+            },
+            BatchKind::Image(_) | BatchKind::Text(_) if !self.images.is_empty() && self.resources.texture(self.kind).is_some() => {
+                self.resources.upload_images(&self.images);
+                if let (Some(texture), Some((vertices, indices))) = (self.resources.texture(self.kind), self.resources.image_buffers().slices(&self.images)) {
+                    let uniforms = uniform! {
+                    matrix: [
+                        [1.0, 0.0, 0.0, 0.0],
+                        [0.0, 1.0, 0.0, 0.0],
+                        [0.0, 0.0, 1.0, 0.0],
+                        [0.0, 0.0, 1.0, 1.0_f32] ],
+                        tex: texture
+                    };
+                    surf.draw(vertices, indices, self.resources.tex_program(), &uniforms, &params).unwrap();
+                }
+            },
+            _ => (),
+        }
+        self.shapes.clear();
+        self.images.clear();
     }
 
-    fn to_color_vertex(color: &Color) -> ColorVertex {
-        ColorVertex{color: [color.r, color.g, color.b, color.a]}
+    /// Cuts a quad (left, top, right, bottom) to the part inside a box, and its texture coordinates with it
+    /// returns:
+    ///     The quad and texture coordinates cut, or None if nothing is inside
+    fn cut_quad(quad: Quad, tex: Quad, bounds: Quad) -> Option<(Quad, Quad)> {
+        let (x1, y1, x2, y2) = quad;
+        let (u1, v1, u2, v2) = tex;
+        let (left, top, right, bottom) = bounds;
+        let (cx1, cy1, cx2, cy2) = (x1.max(left), y1.min(top), x2.min(right), y2.max(bottom));
+        if cx1 >= cx2 || cy1 <= cy2 {
+            return None;
+        }
+        let u = |x: f32| u1 + (u2 - u1) * (x - x1) / (x2 - x1);
+        let v = |y: f32| v1 + (v2 - v1) * (y - y1) / (y2 - y1);
+        Some(((cx1, cy1, cx2, cy2), (u(cx1), v(cy1), u(cx2), v(cy2))))
     }
 
-    fn to_vertex(x: f32, y: f32) -> Vertex {
-        Vertex{position: [x, y]}
+    /// Applies the transform to points
+    fn transform_points(&self, points: &[(f32, f32)]) -> Vec<(f32, f32)> {
+        let transform = self.transform.current();
+        points.iter().map(|p| transform.apply(p.0, p.1)).collect()
     }
 
-    fn to_tex_vertex(x: f32, y: f32, tx: f32, ty: f32) -> TextureVertex {
-        TextureVertex{position: [x, y], tex_coords: [tx, ty]}
+    /// Adds a filled convex polygon to the shapes batch
+    fn add_polygon(&mut self, points: &[(f32, f32)], color: &Color) {
+        let points = self.transform_points(points);
+        self.add_transformed_polygon(&points, color);
     }
-}
 
-impl<'a, 'b> Surface for GLSurface<'a, 'b> {
+    /// Adds a filled convex polygon, whose points are already transformed, to the shapes batch
+    fn add_transformed_polygon(&mut self, points: &[(f32, f32)], color: &Color) {
+        self.use_batch(BatchKind::Shapes);
+        let color = Color{a: color.a * self.opacity(), ..*color};
+        let vertices: Vec<ColorVertex> = points.iter().map(|p| GLSurface::to_color_vertex(p.0, p.1, &color)).collect();
+        self.shapes.add_fan(&vertices);
+    }
 
-    fn load_image(&mut self, location: &str) -> ImageId {
-        let image = GLImage::new(location, &self.display);
-        self.textures.push(image);
-        self.textures.len() - 1
+    /// Adds the segments between consecutive points, one pixel wide, to the shapes batch.
+    /// Every segment is a thin rectangle, so lines and fills can go in the same batch.
+    /// The points are transformed, but the lines stay one pixel wide.
+    fn add_polyline(&mut self, points: &[(f32, f32)], color: &Color) {
+        let points = self.transform_points(points);
+        let (half_x, half_y) = (1_f32 / self.dimensions.0 as f32, 1_f32 / self.dimensions.1 as f32);
+        for segment in points.windows(2) {
+            let ((x1, y1), (x2, y2)) = (segment[0], segment[1]);
+            // Direction of the segment in pixels, to get the half pixel offsets along and across it
+            let (dx, dy) = ((x2 - x1) / half_x, (y2 - y1) / half_y);
+            let length = (dx * dx + dy * dy).sqrt();
+            if length == 0_f32 {
+                continue;
+            }
+            let (ax, ay) = (dx / length * half_x, dy / length * half_y);
+            let (nx, ny) = (-dy / length * half_x, dx / length * half_y);
+            self.add_transformed_polygon(&[
+                (x1 - ax + nx, y1 - ay + ny),
+                (x2 + ax + nx, y2 + ay + ny),
+                (x2 + ax - nx, y2 + ay - ny),
+                (x1 - ax - nx, y1 - ay - ny),
+            ], color);
+        }
     }
-    
+}
+
+impl<'a> Surface for GLSurface<'a> {
+
+    /// Clears the frame. Whatever is waiting in the batches is discarded, as it would be covered.
     fn clear(&mut self, color: &Color)
     {
+        self.shapes.clear();
+        self.images.clear();
         self.raw_surface.as_mut().map(|x| x.clear_color(color.r, color.g, color.b, color.a));
     }
 
     fn draw_line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, line_color: &Color) {
-        let vertices = [
-            GLSurface::to_vertex(x1, y1),
-            GLSurface::to_vertex(x2, y2)
-        ];
-        let colors = glium::VertexBuffer::new(self.display, &[GLSurface::to_color_vertex(line_color); 2]).unwrap(); 
-        let vertex_buffer = glium::VertexBuffer::new(self.display, &vertices).unwrap();
-        let indices = glium::index::NoIndices(glium::index::PrimitiveType::LineStrip);
-        if let Some(surf) = &mut self.raw_surface {
-            surf.draw((&vertex_buffer, &colors), &indices, &self.program, &glium::uniforms::EmptyUniforms, &Default::default()).unwrap();
-        }
+        self.add_polyline(&[(x1, y1), (x2, y2)], line_color);
     }
 
     fn draw_rectangle(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, border_color: Option<&Color>, fill_color: Option<&Color>) {
-        let vertices = [
-            GLSurface::to_vertex(x1, y1),
-            GLSurface::to_vertex(x2, y1),
-            GLSurface::to_vertex(x2, y2),
-            GLSurface::to_vertex(x1, y2),
-            GLSurface::to_vertex(x1, y1),
-        ];
-        let vertex_buffer = glium::VertexBuffer::new(self.display, &vertices).unwrap();
-        if let Some(surf) = &mut self.raw_surface {
-
-            if let Some(fc) = fill_color {
-                let indices = glium::index::NoIndices(glium::index::PrimitiveType::TriangleFan);
-                let colors = glium::VertexBuffer::new(self.display, &[GLSurface::to_color_vertex(fc); 5]).unwrap(); 
-                surf.draw((&vertex_buffer, &colors), &indices, &self.program, &glium::uniforms::EmptyUniforms, &Default::default()).unwrap();
-            }
-
-            if let Some(bc) = border_color {
-                let indices = glium::index::NoIndices(glium::index::PrimitiveType::LineStrip);
-                let colors = glium::VertexBuffer::new(self.display, &[GLSurface::to_color_vertex(bc); 5]).unwrap(); 
-                surf.draw((&vertex_buffer, &colors), &indices, &self.program, &glium::uniforms::EmptyUniforms, &Default::default()).unwrap();
-            }
+        let points = [(x1, y1), (x2, y1), (x2, y2), (x1, y2), (x1, y1)];
+        if let Some(fc) = fill_color {
+            self.add_polygon(&points[..4], fc);
+        }
+        if let Some(bc) = border_color {
+            self.add_polyline(&points, bc);
         }
     }
-    
 
     fn draw_triangle(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x3: f32, y3: f32, border_color: Option<&Color>, fill_color: Option<&Color>){
-        let vertices = [
-            GLSurface::to_vertex(x1, y1),
-            GLSurface::to_vertex(x2, y2),
-            GLSurface::to_vertex(x3, y3),
-            GLSurface::to_vertex(x1, y1),
-        ];
-        let vertex_buffer = glium::VertexBuffer::new(self.display, &vertices).unwrap();
-        
-        if let Some(surf) = &mut self.raw_surface {
-
-            if let Some(fc) = fill_color {
-                let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
-                let colors = glium::VertexBuffer::new(self.display, &[GLSurface::to_color_vertex(fc); 4]).unwrap(); 
-                surf.draw((&vertex_buffer, &colors), &indices, &self.program, &glium::uniforms::EmptyUniforms, &Default::default()).unwrap();
-            }
-            if let Some(bc) = border_color {
-                let indices = glium::index::NoIndices(glium::index::PrimitiveType::LineStrip);
-                let colors = glium::VertexBuffer::new(self.display, &[GLSurface::to_color_vertex(bc); 4]).unwrap(); 
-                surf.draw((&vertex_buffer, &colors), &indices, &self.program, &glium::uniforms::EmptyUniforms, &Default::default()).unwrap();
-            }
+        let points = [(x1, y1), (x2, y2), (x3, y3), (x1, y1)];
+        if let Some(fc) = fill_color {
+            self.add_polygon(&points[..3], fc);
+        }
+        if let Some(bc) = border_color {
+            self.add_polyline(&points, bc);
         }
     }
-    
 
     fn draw_ellipse(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, border_color: Option<&Color>, fill_color: Option<&Color>) {
         let width = x2 - x1;
         let height = y2 - y1;
-        let mut vertices = Vec::new();
+        let mut points = Vec::new();
 
-        let num_segments = cmp::max((width * self.dimensions.0 as f32) as u32, (height * self.dimensions.1 as f32) as u32);
+        // Boxes given from the top, or too small to be seen, still get a closed outline
+        let num_segments = cmp::max((width.abs() * self.dimensions.0 as f32) as u32, (height.abs() * self.dimensions.1 as f32) as u32)
+            .max(8_u32);
         let theta = 2f32 * std::f32::consts::PI / num_segments as f32; 
         let c = theta.cos();
         let s = theta.sin();
@@ -171,161 +262,198 @@ impl<'a, 'b> Surface for GLSurface<'a, 'b> {
 
         let mut t;
         for _ in 0 ..= num_segments  { 
-            vertices.push(GLSurface::to_vertex(px * rx + cx, py * ry + cy));
+            points.push((px * rx + cx, py * ry + cy));
     
             t = px;
             px = c * px - s * py;
             py = s * t + c * py;
         } 
-        let vertex_buffer = glium::VertexBuffer::new(self.display, &vertices).unwrap();
-        if let Some(surf) = &mut self.raw_surface {
-
-            if let Some(fc) = fill_color {
-                let indices = glium::index::NoIndices(glium::index::PrimitiveType::TriangleFan);
-                let colors = vec![GLSurface::to_color_vertex(fc); vertex_buffer.len()];
-                let colors_buffer = glium::VertexBuffer::new(self.display, &colors).unwrap(); 
-                surf.draw((&vertex_buffer, &colors_buffer), &indices, &self.program, &glium::uniforms::EmptyUniforms, &Default::default()).unwrap();
-            }
-            if let Some(bc) = border_color {
-                let indices = glium::index::NoIndices(glium::index::PrimitiveType::LineStrip);
-                let colors = vec![GLSurface::to_color_vertex(bc); vertex_buffer.len()];
-                let colors_buffer = glium::VertexBuffer::new(self.display, &colors).unwrap(); 
-                surf.draw((&vertex_buffer, &colors_buffer), &indices, &self.program, &glium::uniforms::EmptyUniforms, &Default::default()).unwrap();
-            }
+        if let Some(fc) = fill_color {
+            self.add_polygon(&points, fc);
+        }
+        if let Some(bc) = border_color {
+            self.add_polyline(&points, bc);
         }
-
     }
 
     fn draw_circle(&mut self, x: f32, y: f32, radius: f32, border_color: Option<&Color>, fill_color: Option<&Color>) {
-        self.draw_ellipse(x, y, radius, radius, border_color, fill_color);
+        let vertical = radius * self.dimensions.0 as f32 / self.dimensions.1 as f32;
+        self.draw_ellipse(x - radius, y + vertical, x + radius, y - vertical, border_color, fill_color);
     }
 
     fn draw_rounded_rectangle(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, rad_pixel: u32, border_color: Option<&Color>, fill_color: Option<&Color>) {
-        /*
-        let h_pad = (rad_prop.clamp(0.0_f32, 0.5_f32) * width as f32) as u32;
-        let v_pad = (rad_prop.clamp(0.0_f32, 0.5_f32) * height as f32) as u32;
-        let x1_prop = x + h_pad;
-        let x2_prop = ((x + width) as i32 - h_pad as i32) as u32;
-        let y1_prop = y + v_pad;
-        let y2_prop = ((y + height) as i32 - v_pad as i32) as u32;
-
-        let num_segments = cmp::max(h_pad, v_pad);
-        let theta = 0.5_f32 * std::f32::consts::PI / num_segments as f32; 
-        let c = theta.cos();
-        let s = theta.sin();
-        let rx = h_pad as f32;
-        let ry = v_pad as f32;
-        let mut t;
-    
-        let (r, g, b) = GLSurface::translate_color(border_color);
-        let mut vertices = Vec::new();
-        vertices.push(self.translate_point(x1_prop, y));
-        vertices.push(self.translate_point(x2_prop, y));
-
-        let mut px = 0_f32; 
-        let mut py = -1_f32;
-        let mut cx = x2_prop as f32;
-        let mut cy = y1_prop as f32;
-        for _ in 0 ..= num_segments  { 
-            vertices.push(self.translate_point((px * rx + cx) as u32, (py * ry + cy) as u32)); 
+        let (left, right, bottom, top) = (x1.min(x2), x1.max(x2), y1.min(y2), y1.max(y2));
+        let (pixel_x, pixel_y) = (2_f32 / self.dimensions.0 as f32, 2_f32 / self.dimensions.1 as f32);
+        let radius = (rad_pixel as f32)
+            .min((right - left) / pixel_x * 0.5_f32)
+            .min((top - bottom) / pixel_y * 0.5_f32);
+        let (rx, ry) = (radius * pixel_x, radius * pixel_y);
+        let num_segments = (radius as u32).max(2_u32);
+        let corners = [(right - rx, top - ry), (left + rx, top - ry), (left + rx, bottom + ry), (right - rx, bottom + ry)];
+
+        // Quarters of circle counterclockwise from the right side of the top right corner
+        let mut points = Vec::new();
+        for (corner, (cx, cy)) in corners.iter().enumerate() {
+            for i in 0..=num_segments {
+                let angle = 0.5_f32 * std::f32::consts::PI * (corner as f32 + i as f32 / num_segments as f32);
+                points.push((cx + rx * angle.cos(), cy + ry * angle.sin()));
+            }
+        }
+        if let Some(fc) = fill_color {
+            self.add_polygon(&points, fc);
+        }
+        if let Some(bc) = border_color {
+            points.push(points[0]);
+            self.add_polyline(&points, bc);
+        }
+    }
 
-            t = px;
-            px = c * px - s * py;
-            py = s * t + c * py;
-        } 
+    /// The glyphs are quads of the texture of the font, cut by the box, so the text is batched like an image
+    fn draw_text(&mut self, text: &str, x1: f32, y1: f32, x2: f32, y2: f32, text_color: &Color, style: &TextStyle) {
+        let font = match self.resources.fonts().get(style.font) {
+            Some(font) => font,
+            None => return,
+        };
+        let (left, top, right, bottom) = (x1.min(x2), y1.max(y2), x1.max(x2), y1.min(y2));
+        let scale_y = style.size * 2_f32 / self.dimensions.1 as f32;
+        let scale_x = style.size * 2_f32 / self.dimensions.0 as f32;
+        let baseline = top - font.ascent() * scale_y;
+        let offsets: &[f32] = match style.weight {
+            FontWeight::Regular => &[0_f32],
+            FontWeight::Bold => &[0_f32, 2_f32 / self.dimensions.0 as f32],
+        };
+        let mut quads = Vec::new();
+        for offset in offsets {
+            let mut pen = left + offset;
+            for glyph in text.chars().filter_map(|x| font.glyph(x)) {
+                let gx1 = pen + glyph.left * scale_x;
+                let gy1 = baseline + glyph.top * scale_y;
+                let quad = (gx1, gy1, gx1 + glyph.width * scale_x, gy1 - glyph.height * scale_y);
+                if let Some(quad) = GLSurface::cut_quad(quad, glyph.tex, (left, top, right, bottom)) {
+                    quads.push(quad);
+                }
+                pen += glyph.advance * scale_x;
+            }
+        }
+        if quads.is_empty() {
+            return;
+        }
 
-        vertices.push(self.translate_point(x + width, y1_prop));
-        vertices.push(self.translate_point(x + width, y2_prop));
+        self.use_batch(BatchKind::Text(style.font));
+        let color = [text_color.r, text_color.g, text_color.b, text_color.a * self.opacity()];
+        for ((qx1, qy1, qx2, qy2), (u1, v1, u2, v2)) in quads {
+            let points = self.transform_points(&[(qx1, qy1), (qx2, qy1), (qx2, qy2), (qx1, qy2)]);
+            let tex_coords = [(u1, v1), (u2, v1), (u2, v2), (u1, v2)];
+            let vertices: Vec<TextureVertex> = points.iter().zip(tex_coords.iter())
+                .map(|(p, t)| GLSurface::to_tex_vertex(p.0, p.1, t.0, t.1, color))
+                .collect();
+            self.images.add_fan(&vertices);
+        }
+    }
 
-        px = 1_f32; 
-        py = 0_f32;
-        cx = x2_prop as f32;
-        cy = y2_prop as f32;
-        for _ in 0 ..= num_segments  { 
-            vertices.push(self.translate_point((px * rx + cx) as u32, (py * ry + cy) as u32)); 
+    fn push_opacity(&mut self, opacity: f32) {
+        let current = self.opacity();
+        self.opacity.push(current * opacity.clamp(0_f32, 1_f32));
+    }
 
-            t = px;
-            px = c * px - s * py;
-            py = s * t + c * py;
-        } 
+    fn pop_opacity(&mut self) {
+        self.opacity.pop();
+    }
 
-        vertices.push(self.translate_point(x2_prop, y + height));
-        vertices.push(self.translate_point(x1_prop, y + height));
+    /// The geometry pending was clipped with the previous rectangle, so it is drawn first
+    fn push_clip(&mut self, x1: f32, y1: f32, x2: f32, y2: f32) {
+        self.flush();
+        let (x1, y1, x2, y2) = self.transform.current().apply_bounds(x1, y1, x2, y2);
+        let rect = self.clip_rect(self.to_pixel_rect(x1, y1, x2, y2));
+        self.clip.push(rect);
+    }
 
-        px = 0_f32; 
-        py = 1_f32;
-        cx = x1_prop as f32;
-        cy = y2_prop as f32;
-        for _ in 0 ..= num_segments  { 
-            vertices.push(self.translate_point((px * rx + cx) as u32, (py * ry + cy) as u32)); 
+    fn pop_clip(&mut self) {
+        self.flush();
+        self.clip.pop();
+    }
 
-            t = px;
-            px = c * px - s * py;
-            py = s * t + c * py;
-        } 
+    fn push_transform(&mut self) {
+        self.transform.push();
+    }
 
-        vertices.push(self.translate_point(x, y2_prop));
-        vertices.push(self.translate_point(x, y1_prop));
+    fn pop_transform(&mut self) {
+        self.transform.pop();
+    }
 
-        px = -1_f32; 
-        py = 0_f32;
-        cx = x1_prop as f32;
-        cy = y1_prop as f32;
-        for _ in 0 ..= num_segments  { 
-            vertices.push(self.translate_point((px * rx + cx) as u32, (py * ry + cy) as u32)); 
+    fn translate(&mut self, x: f32, y: f32) {
+        self.transform.translate(x, y);
+    }
 
-            t = px;
-            px = c * px - s * py;
-            py = s * t + c * py;
-        } 
+    fn scale(&mut self, x: f32, y: f32) {
+        self.transform.scale(x, y);
+    }
 
-        vertices.push(self.translate_point(x1_prop, y));
+    fn rotate(&mut self, angle: f32) {
+        self.transform.rotate(angle, self.dimensions.0 as f32 / self.dimensions.1 as f32);
+    }
 
-        let vertex_buffer = glium::VertexBuffer::new(self.display, &vertices).unwrap();
-        let colors = vec![ColorVertex { color: [r, g, b] }; vertex_buffer.len()];
-        let colors_buffer = glium::VertexBuffer::new(self.display, &colors).unwrap(); 
-        let indices = glium::index::NoIndices(glium::index::PrimitiveType::LineStrip);
-        if let Some(surf) = &mut self.raw_surface {
-            surf.draw((&vertex_buffer, &colors_buffer), &indices, &self.program, &glium::uniforms::EmptyUniforms, &Default::default()).unwrap();
+    fn draw_image_region(&mut self, img: ImageId, x1: f32, y1: f32, x2: f32, y2: f32, region: (f32, f32, f32, f32)) {
+        if self.resources.image(img).is_none() {
+            return;
         }
-        */
+        self.use_batch(BatchKind::Image(img));
+
+        // The texture is stored upside down
+        let (u1, v1, u2, v2) = (region.0, 1_f32 - region.1, region.2, 1_f32 - region.3);
+        let color = [1_f32, 1_f32, 1_f32, self.opacity()];
+        let points = self.transform_points(&[(x1, y1), (x2, y1), (x2, y2), (x1, y2)]);
+        let tex_coords = [(u1, v1), (u2, v1), (u2, v2), (u1, v2)];
+        let vertices: Vec<TextureVertex> = points.iter().zip(tex_coords.iter())
+            .map(|(p, t)| GLSurface::to_tex_vertex(p.0, p.1, t.0, t.1, color))
+            .collect();
+        self.images.add_fan(&vertices);
     }
+}
 
+impl<'a> ImageProvider for GLSurface<'a> {
 
-    fn draw_text(&mut self, text: &str, x1: f32, y1: f32, x2: f32, y2: f32, text_color: &Color) {
+    fn load_image_from_rgba(&mut self, width: u32, height: u32, pixels: Vec<u8>) -> Result<ImageId, ImageError> {
+        self.resources.load_image_from_rgba(width, height, pixels)
+    }
 
+    fn image_size(&self, img: ImageId) -> Option<(u32, u32)> {
+        self.resources.image_size(img)
     }
+}
 
-    fn draw_image(&mut self, img: ImageId, x1: f32, y1: f32, x2: f32, y2: f32) {
-        if img >= self.textures.len() {
-            return;
-        }
+impl<'a> FontProvider for GLSurface<'a> {
 
-        let vertices = [
-            GLSurface::to_tex_vertex(x1, y1, 0_f32, 1_f32),
-            GLSurface::to_tex_vertex(x2, y1, 1_f32, 1_f32),
-            GLSurface::to_tex_vertex(x2, y2, 1_f32, 0_f32),
-            GLSurface::to_tex_vertex(x1, y2, 0_f32, 0_f32),
-        ];
-        let vertex_buffer = glium::VertexBuffer::new(self.display, &vertices).unwrap();
-        if let Some(surf) = &mut self.raw_surface {
-            let uniforms = uniform! {
-            matrix: [
-                [1.0, 0.0, 0.0, 0.0],
-                [0.0, 1.0, 0.0, 0.0],
-                [0.0, 0.0, 1.0, 0.0],
-                [0.0, 0.0, 1.0, 1.0_f32] ],
-                tex: &self.textures[img].texture
-            };
-            let indices = glium::index::NoIndices(glium::index::PrimitiveType::TriangleStrip);
-            surf.draw(&vertex_buffer, &indices, &self.tex_program, &uniforms, &Default::default()).unwrap();
-        }
+    fn load_font_from_bytes(&mut self, bytes: Vec<u8>) -> Result<FontId, FontError> {
+        self.resources.load_font_from_bytes(bytes)
+    }
 
+    fn measure_text(&self, text: &str, style: &TextStyle) -> TextMetrics {
+        self.resources.measure_text(text, style)
     }
 }
 
-impl<'a, 'b> Drop for GLSurface<'a, 'b> {
+impl<'a> Drop for GLSurface<'a> {
     fn drop(&mut self) {
+        self.flush();
         self.raw_surface.take().unwrap().finish().unwrap();
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quads_are_cut_with_their_texture() {
+        let tex = (0_f32, 1_f32, 1_f32, 0_f32);
+        let bounds = (-1_f32, 1_f32, 0.5_f32, 0_f32);
+        let inside = GLSurface::cut_quad((-0.5_f32, 0.5_f32, 0_f32, 0.25_f32), tex, bounds);
+        assert_eq!(inside, Some(((-0.5_f32, 0.5_f32, 0_f32, 0.25_f32), tex)));
+
+        // The right half and the bottom half are out of the box
+        let cut = GLSurface::cut_quad((0_f32, 0.5_f32, 1_f32, -0.5_f32), tex, bounds);
+        assert_eq!(cut, Some(((0_f32, 0.5_f32, 0.5_f32, 0_f32), (0_f32, 1_f32, 0.5_f32, 0.5_f32))));
+
+        assert_eq!(GLSurface::cut_quad((0.6_f32, 0.5_f32, 1_f32, 0.25_f32), tex, bounds), None);
+    }
+}
@@ -16,17 +16,21 @@ limitations under the License.
 
 extern crate glium;
 
-use crate::{Screen, Window, Color, Surface, Space, ScreenId, UpdateData, MouseStatus, KeyboardStatus};
+use crate::{Chord, CommandId};
+use crate::{Screen, ScreenStack, Window, Color, Surface, ScreenId, UpdateData, MouseStatus, Key, App, Event};
+use crate::{FontId, TextStyle, TextMetrics, FontError, FontProvider};
+use crate::{ImageId, ImageError, ImageProvider};
 use crate::gl::gl_surface::GLSurface;
+use crate::gl::gl_resources::GLResources;
+
+/// Pixels scrolled by touchpads that count as a line of the mouse wheel
+const WHEEL_LINE_PIXELS: f32 = 20_f32;
 
 /// OpenGL implementation for Window
 pub struct GLWindow{
     event_loop: glium::glutin::event_loop::EventLoop<()>,
-    display: Option<glium::Display>,
-    program: Option<glium::Program>,
-    program_textures: Option<glium::Program>,
-    screens: Vec<Screen>,
-    dimensions: (u32, u32)
+    resources: GLResources,
+    screens: ScreenStack,
 }
 
 impl GLWindow {
@@ -46,66 +50,12 @@ impl GLWindow {
         let context_builder = glium::glutin::ContextBuilder::new();
         let display = glium::Display::new(window_builder, context_builder, &event_loop).unwrap();
 
-        let vertex_shader_src = r#"
-        #version 140
-
-        in vec2 position;
-        in vec4 color;
-        out vec4 v_color;
-
-        void main() {
-            gl_Position = vec4(position, 0.0, 1.0);
-            v_color = color;
-        }
-        "#;
-        let fragment_shader_src = r#"
-            #version 140
-
-            in vec4 v_color;
-
-            void main() {
-                gl_FragColor = v_color;
-            }
-        "#;
-        let program = glium::Program::from_source(&display, vertex_shader_src, fragment_shader_src, None).unwrap();
-
-        let vertex_shader_src = r#"
-        #version 140
-
-        in vec2 position;
-        in vec2  tex_coords;
-        out vec2 v_tex_coords;
-
-        uniform mat4 matrix;
-
-        void main() {
-            v_tex_coords = tex_coords;
-            gl_Position = matrix * vec4(position, 0.0, 1.0);
-        }
-        "#;
-
-        let fragment_shader_src = r#"
-            #version 140
-
-            in vec2 v_tex_coords;
-            out vec4 v_color;
-
-            uniform sampler2D tex;
-
-            void main() {
-                v_color = texture(tex, v_tex_coords);
-            }
-        "#;
-        let tex_program = glium::Program::from_source(&display, vertex_shader_src, fragment_shader_src, None).unwrap();
+        let resources = GLResources::new(&display);
 
         GLWindow {
             event_loop,
-            display: Some(display),
-            program: Some(program),
-            program_textures : Some(tex_program),
-            //surface: Some(GLSurface::new(&display, &program, &tex_program)),
-            screens: Vec::new(),
-            dimensions: (width, height)
+            resources,
+            screens: ScreenStack::new(width, height),
         }
     }
 
@@ -113,22 +63,20 @@ impl GLWindow {
 
 impl Window for GLWindow {
 
-    fn add_screen(&mut self, mut screen: Screen) -> ScreenId {
-        screen.resize(Space::new(self.dimensions.0, self.dimensions.1));
-        self.screens.push(screen);
-        self.screens.len()
+    fn add_screen(&mut self, screen: Screen) -> ScreenId {
+        self.screens.add_screen(screen)
+    }
+
+    fn add_accelerator(&mut self, chords: &[Chord], command: CommandId) {
+        self.screens.add_accelerator(chords, command);
     }
 
-    fn execute(mut self) {
+    fn execute(self, mut app: Box<dyn App>) {
 
         let event_loop = self.event_loop;
-        let display = std::mem::replace(&mut self.display, None).unwrap();
-        let program = std::mem::replace(&mut self.program, None).unwrap();
-        let program_tex = std::mem::replace(&mut self.program_textures, None).unwrap();
-        let mut screens = std::mem::replace(&mut self.screens, Vec::new());
-        let current_screen: usize = 0;
-        let mut update_data = UpdateData{mouse_position: (0_u32, 0_u32), mouse_status: MouseStatus::Idle};
-        let mut dimensions = self.dimensions;
+        let mut resources = self.resources;
+        let mut screens = self.screens;
+        let mut update_data = UpdateData::new();
 
 
         event_loop.run(move |ev, _, control_flow| {
@@ -138,34 +86,181 @@ impl Window for GLWindow {
             match ev {
                 glium::glutin::event::Event::WindowEvent { event, .. } => match event {
                     glium::glutin::event::WindowEvent::CloseRequested => {
+                        app.handle_event(Event::Quit());
                         *control_flow = glium::glutin::event_loop::ControlFlow::Exit;
                         return;
                     },
-                    glium::glutin::event::WindowEvent::CursorMoved{ device_id, position, modifiers } => {
+                    glium::glutin::event::WindowEvent::CursorMoved{ position, .. } => {
                         let my_position = (position.x as u32, position.y as u32);
                         update_data.mouse_position = my_position;
                     },
-                    glium::glutin::event::WindowEvent::Resized( size ) => {
-                        dimensions = (size.width, size.height);
-                        if current_screen <= screens.len() {
-                            screens[current_screen].resize(Space::new(size.width, size.height));
+                    glium::glutin::event::WindowEvent::MouseInput{ state, button: glium::glutin::event::MouseButton::Left, .. } => {
+                        update_data.mouse_status = match state {
+                            glium::glutin::event::ElementState::Pressed => MouseStatus::Pressed,
+                            glium::glutin::event::ElementState::Released => MouseStatus::Released,
+                        };
+                    },
+                    glium::glutin::event::WindowEvent::MouseWheel{ delta, .. } => {
+                        update_data.wheel += match delta {
+                            glium::glutin::event::MouseScrollDelta::LineDelta(_, lines) => lines,
+                            glium::glutin::event::MouseScrollDelta::PixelDelta(position) => position.y as f32 / WHEEL_LINE_PIXELS,
+                        };
+                    },
+                    glium::glutin::event::WindowEvent::KeyboardInput{ input, .. } => {
+                        let key = input.virtual_keycode.map(translate_key).unwrap_or(Key::Other);
+                        match input.state {
+                            // glutin sends a new press for every repetition of a held key
+                            glium::glutin::event::ElementState::Pressed => update_data.press_key(key, input.scancode),
+                            glium::glutin::event::ElementState::Released => update_data.release_key(key, input.scancode),
+                        }
+                    },
+                    glium::glutin::event::WindowEvent::ReceivedCharacter( character ) => {
+                        if !character.is_control() {
+                            update_data.text.push(character);
                         }
                     },
+                    glium::glutin::event::WindowEvent::Focused( false ) => {
+                        update_data.lose_focus();
+                    },
+                    glium::glutin::event::WindowEvent::Resized( size ) => {
+                        screens.resize(size.width, size.height);
+                    },
     
                     _ => return,
                 },
+                glium::glutin::event::Event::DeviceEvent { event: glium::glutin::event::DeviceEvent::ModifiersChanged(state), .. } => {
+                    update_data.modifiers.shift = state.shift();
+                    update_data.modifiers.ctrl = state.ctrl();
+                    update_data.modifiers.alt = state.alt();
+                    update_data.modifiers.logo = state.logo();
+                },
                 _ => (),
             }
 
-            let mut surface = GLSurface::new(
-                &display, 
-                &program, 
-                &program_tex);
+            let mut surface = GLSurface::new(&mut resources);
             surface.clear(&Color{r: 0_f32, g: 0_f32, b: 0_f32, a: 1_f32});
-            if current_screen <= screens.len() {
-                screens[current_screen].update(&update_data);
-                screens[current_screen].paint(&mut surface);
+            for event in screens.update(&update_data) {
+                for message in app.handle_event(event) {
+                    screens.process_message(&message);
+                }
             }
+            update_data.reset();
+            screens.paint(&mut surface);
         });
     }
-}
\ No newline at end of file
+}
+
+impl FontProvider for GLWindow {
+
+    fn load_font_from_bytes(&mut self, bytes: Vec<u8>) -> Result<FontId, FontError> {
+        self.resources.load_font_from_bytes(bytes)
+    }
+
+    fn measure_text(&self, text: &str, style: &TextStyle) -> TextMetrics {
+        self.resources.measure_text(text, style)
+    }
+}
+
+impl ImageProvider for GLWindow {
+
+    fn load_image_from_rgba(&mut self, width: u32, height: u32, pixels: Vec<u8>) -> Result<ImageId, ImageError> {
+        self.resources.load_image_from_rgba(width, height, pixels)
+    }
+
+    fn image_size(&self, img: ImageId) -> Option<(u32, u32)> {
+        self.resources.image_size(img)
+    }
+}
+
+/// Translates a glutin key into a key for the widgets
+fn translate_key(code: glium::glutin::event::VirtualKeyCode) -> Key {
+    use glium::glutin::event::VirtualKeyCode;
+    match code {
+        VirtualKeyCode::A => Key::A,
+        VirtualKeyCode::B => Key::B,
+        VirtualKeyCode::C => Key::C,
+        VirtualKeyCode::D => Key::D,
+        VirtualKeyCode::E => Key::E,
+        VirtualKeyCode::F => Key::F,
+        VirtualKeyCode::G => Key::G,
+        VirtualKeyCode::H => Key::H,
+        VirtualKeyCode::I => Key::I,
+        VirtualKeyCode::J => Key::J,
+        VirtualKeyCode::K => Key::K,
+        VirtualKeyCode::L => Key::L,
+        VirtualKeyCode::M => Key::M,
+        VirtualKeyCode::N => Key::N,
+        VirtualKeyCode::O => Key::O,
+        VirtualKeyCode::P => Key::P,
+        VirtualKeyCode::Q => Key::Q,
+        VirtualKeyCode::R => Key::R,
+        VirtualKeyCode::S => Key::S,
+        VirtualKeyCode::T => Key::T,
+        VirtualKeyCode::U => Key::U,
+        VirtualKeyCode::V => Key::V,
+        VirtualKeyCode::W => Key::W,
+        VirtualKeyCode::X => Key::X,
+        VirtualKeyCode::Y => Key::Y,
+        VirtualKeyCode::Z => Key::Z,
+        VirtualKeyCode::Key0 | VirtualKeyCode::Numpad0 => Key::Num0,
+        VirtualKeyCode::Key1 | VirtualKeyCode::Numpad1 => Key::Num1,
+        VirtualKeyCode::Key2 | VirtualKeyCode::Numpad2 => Key::Num2,
+        VirtualKeyCode::Key3 | VirtualKeyCode::Numpad3 => Key::Num3,
+        VirtualKeyCode::Key4 | VirtualKeyCode::Numpad4 => Key::Num4,
+        VirtualKeyCode::Key5 | VirtualKeyCode::Numpad5 => Key::Num5,
+        VirtualKeyCode::Key6 | VirtualKeyCode::Numpad6 => Key::Num6,
+        VirtualKeyCode::Key7 | VirtualKeyCode::Numpad7 => Key::Num7,
+        VirtualKeyCode::Key8 | VirtualKeyCode::Numpad8 => Key::Num8,
+        VirtualKeyCode::Key9 | VirtualKeyCode::Numpad9 => Key::Num9,
+        VirtualKeyCode::F1 => Key::F1,
+        VirtualKeyCode::F2 => Key::F2,
+        VirtualKeyCode::F3 => Key::F3,
+        VirtualKeyCode::F4 => Key::F4,
+        VirtualKeyCode::F5 => Key::F5,
+        VirtualKeyCode::F6 => Key::F6,
+        VirtualKeyCode::F7 => Key::F7,
+        VirtualKeyCode::F8 => Key::F8,
+        VirtualKeyCode::F9 => Key::F9,
+        VirtualKeyCode::F10 => Key::F10,
+        VirtualKeyCode::F11 => Key::F11,
+        VirtualKeyCode::F12 => Key::F12,
+        VirtualKeyCode::Left => Key::Left,
+        VirtualKeyCode::Right => Key::Right,
+        VirtualKeyCode::Up => Key::Up,
+        VirtualKeyCode::Down => Key::Down,
+        VirtualKeyCode::Home => Key::Home,
+        VirtualKeyCode::End => Key::End,
+        VirtualKeyCode::PageUp => Key::PageUp,
+        VirtualKeyCode::PageDown => Key::PageDown,
+        VirtualKeyCode::Insert => Key::Insert,
+        VirtualKeyCode::Back => Key::Backspace,
+        VirtualKeyCode::Delete => Key::Delete,
+        VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => Key::Enter,
+        VirtualKeyCode::Escape => Key::Escape,
+        VirtualKeyCode::Tab => Key::Tab,
+        VirtualKeyCode::Space => Key::Space,
+        VirtualKeyCode::LShift | VirtualKeyCode::RShift => Key::Shift,
+        VirtualKeyCode::LControl | VirtualKeyCode::RControl => Key::Ctrl,
+        VirtualKeyCode::LAlt | VirtualKeyCode::RAlt => Key::Alt,
+        VirtualKeyCode::LWin | VirtualKeyCode::RWin => Key::Logo,
+        _ => Key::Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glium::glutin::event::VirtualKeyCode;
+
+    #[test]
+    fn keys_are_translated() {
+        assert_eq!(translate_key(VirtualKeyCode::A), Key::A);
+        assert_eq!(translate_key(VirtualKeyCode::Key7), Key::Num7);
+        assert_eq!(translate_key(VirtualKeyCode::Numpad7), Key::Num7);
+        assert_eq!(translate_key(VirtualKeyCode::NumpadEnter), Key::Enter);
+        assert_eq!(translate_key(VirtualKeyCode::Back), Key::Backspace);
+        assert_eq!(translate_key(VirtualKeyCode::RShift), Key::Shift);
+        assert_eq!(translate_key(VirtualKeyCode::LWin), Key::Logo);
+        assert_eq!(translate_key(VirtualKeyCode::Mute), Key::Other);
+    }
+}
@@ -17,6 +17,9 @@ limitations under the License.
 mod gl_surface;
 mod gl_window;
 mod gl_image;
+mod gl_font;
+mod gl_resources;
+mod gl_batch;
 
 pub fn create_window(caption: &str, width: u32, height: u32, resizable: bool) -> gl_window::GLWindow {
     gl_window::GLWindow::new(caption, width, height, resizable)
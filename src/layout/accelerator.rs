@@ -0,0 +1,207 @@
+/*
+Copyright 2021 Gonzalo Fernández Hernández
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::{UpdateData, KeyboardStatus, Key, Modifiers, CommandId, CommandState, Message};
+
+/// A key pressed with some modifiers, like Ctrl+S
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Chord {
+    pub key: Key,
+    pub modifiers: Modifiers,
+}
+
+impl Chord {
+    pub fn new(key: Key, modifiers: Modifiers) -> Self {
+        Chord{key, modifiers}
+    }
+
+    /// The key alone, like F5
+    pub fn key(key: Key) -> Self {
+        Chord::new(key, Modifiers::default())
+    }
+
+    /// The key with Ctrl, like Ctrl+S
+    pub fn ctrl(key: Key) -> Self {
+        Chord::new(key, Modifiers{ctrl: true, ..Modifiers::default()})
+    }
+
+    /// Text of a sequence of chords, like "Ctrl+K, Ctrl+C"
+    pub fn sequence_text(chords: &[Chord]) -> String {
+        chords.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(", ")
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let modifiers = [
+            (self.modifiers.ctrl, "Ctrl+"),
+            (self.modifiers.alt, "Alt+"),
+            (self.modifiers.shift, "Shift+"),
+            (self.modifiers.logo, "Logo+"),
+        ];
+        for (_, name) in modifiers.iter().filter(|x| x.0) {
+            write!(f, "{}", name)?;
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+/// Table of key chords, or sequences of chords, that request commands.
+/// The commands disabled or hidden with CommandAvailable are ignored.
+///     * entries: The sequences and their commands
+///     * pending: The chords pressed that begin a sequence
+///     * disabled: The commands that can not be requested now
+#[derive(Default)]
+pub struct Accelerators {
+    entries: Vec<(Vec<Chord>, CommandId)>,
+    pending: Vec<Chord>,
+    disabled: HashSet<CommandId>,
+}
+
+impl Accelerators {
+    pub fn new() -> Self {
+        Accelerators::default()
+    }
+
+    /// Adds a sequence of chords, like Ctrl+K Ctrl+C, that requests a command.
+    /// A single chord is a sequence of one.
+    pub fn add(&mut self, chords: &[Chord], command: CommandId) {
+        if !chords.is_empty() {
+            self.entries.push((chords.to_vec(), command));
+        }
+    }
+
+    /// Checks the key pressed in an update.
+    /// Repeated keys and modifier keys alone are ignored, so they do not break a sequence.
+    /// returns:
+    ///     The command requested, when a sequence is completed
+    pub fn process(&mut self, data: &UpdateData) -> Option<CommandId> {
+        let key = match data.keyboard_status {
+            KeyboardStatus::KeyDown(key) if !data.key_repeat => key,
+            _ => return None,
+        };
+        if matches!(key, Key::Shift | Key::Ctrl | Key::Alt | Key::Logo | Key::Other) {
+            return None;
+        }
+        let chord = Chord::new(key, data.modifiers);
+        self.pending.push(chord);
+        if !self.is_prefix() {
+            // The chord may begin a new sequence
+            self.pending.clear();
+            self.pending.push(chord);
+            if !self.is_prefix() {
+                self.pending.clear();
+                return None;
+            }
+        }
+        let command = self.entries.iter().find(|x| x.0 == self.pending).map(|x| x.1);
+        if command.is_some() {
+            self.pending.clear();
+        }
+        command.filter(|x| !self.disabled.contains(x))
+    }
+
+    /// Keeps the state of the commands given by the app
+    pub fn process_message(&mut self, message: &Message) {
+        if let Message::CommandAvailable(command, state) = message {
+            if *state == CommandState::Enabled {
+                self.disabled.remove(command);
+            } else {
+                self.disabled.insert(*command);
+            }
+        }
+    }
+
+    /// Whether the chords pending begin some sequence
+    fn is_prefix(&self) -> bool {
+        self.entries.iter().any(|x| x.0.starts_with(&self.pending))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accelerators() -> Accelerators {
+        let mut accelerators = Accelerators::new();
+        accelerators.add(&[Chord::ctrl(Key::K), Chord::ctrl(Key::C)], 1);
+        accelerators.add(&[Chord::ctrl(Key::S)], 2);
+        accelerators
+    }
+
+    /// Input of an update where a key is pressed with some modifiers
+    fn press(chord: Chord, repeat: bool) -> UpdateData {
+        let mut data = UpdateData::new();
+        data.keyboard_status = KeyboardStatus::KeyDown(chord.key);
+        data.key_repeat = repeat;
+        data.modifiers = chord.modifiers;
+        data
+    }
+
+    fn process(accelerators: &mut Accelerators, chords: &[Chord]) -> Vec<Option<CommandId>> {
+        chords.iter().map(|x| accelerators.process(&press(*x, false))).collect()
+    }
+
+    #[test]
+    fn sequence_requests_its_command() {
+        let mut accelerators = accelerators();
+        let result = process(&mut accelerators, &[Chord::ctrl(Key::K), Chord::ctrl(Key::C)]);
+        assert_eq!(result, vec![None, Some(1)]);
+        assert_eq!(process(&mut accelerators, &[Chord::ctrl(Key::S)]), vec![Some(2)]);
+    }
+
+    #[test]
+    fn broken_prefix_is_reset() {
+        let mut accelerators = accelerators();
+        let result = process(&mut accelerators, &[Chord::ctrl(Key::K), Chord::key(Key::A), Chord::ctrl(Key::C)]);
+        assert_eq!(result, vec![None, None, None]);
+        // The chord that breaks a sequence can begin another one
+        let result = process(&mut accelerators, &[Chord::ctrl(Key::K), Chord::ctrl(Key::S)]);
+        assert_eq!(result, vec![None, Some(2)]);
+    }
+
+    #[test]
+    fn repeats_and_modifier_keys_do_not_break_a_sequence() {
+        let mut accelerators = accelerators();
+        assert_eq!(accelerators.process(&press(Chord::ctrl(Key::K), false)), None);
+        assert_eq!(accelerators.process(&press(Chord::ctrl(Key::K), true)), None);
+        assert_eq!(accelerators.process(&press(Chord::ctrl(Key::Ctrl), false)), None);
+        assert_eq!(accelerators.process(&press(Chord::ctrl(Key::C), false)), Some(1));
+    }
+
+    #[test]
+    fn disabled_commands_are_ignored() {
+        let mut accelerators = accelerators();
+        accelerators.process_message(&Message::CommandAvailable(1, CommandState::Disabled));
+        let result = process(&mut accelerators, &[Chord::ctrl(Key::K), Chord::ctrl(Key::C)]);
+        assert_eq!(result, vec![None, None]);
+        assert_eq!(process(&mut accelerators, &[Chord::ctrl(Key::S)]), vec![Some(2)]);
+
+        accelerators.process_message(&Message::CommandAvailable(1, CommandState::Enabled));
+        let result = process(&mut accelerators, &[Chord::ctrl(Key::K), Chord::ctrl(Key::C)]);
+        assert_eq!(result, vec![None, Some(1)]);
+    }
+
+    #[test]
+    fn sequence_text_joins_the_chords() {
+        let chords = [Chord::ctrl(Key::K), Chord::new(Key::C, Modifiers{ctrl: true, shift: true, ..Modifiers::default()})];
+        assert_eq!(Chord::sequence_text(&chords), "Ctrl+K, Ctrl+Shift+C");
+    }
+}
@@ -18,6 +18,7 @@ use crate::{Direction, Sense};
 use crate::Widget;
 use crate::Space;
 use crate::Panel;
+use crate::Surface;
 
 /// Describes an operation to divide the space. 
 /// This is an intermediate node in the layout tree.
@@ -192,71 +193,35 @@ impl Layout {
 		}
 	}
 
-	pub fn get_widgets(&self) -> Vec<&Widget> {
+	/// Paints the panels and the widgets in tree order. The content of a Group is clipped to its panel.
+	pub fn paint(&self, surface: &mut dyn Surface) {
 		match self {
 			Layout::Operation{op, dir: _dir, sense: _sense} => {
 				match op {
-					LayoutOperation::Nop => Vec::new(),
-					LayoutOperation::Split{proportion: _prop, first, second} => {
-						let mut result = first.get_widgets();
-						result.append(&mut second.get_widgets());
-						result
-					},
-					LayoutOperation::Divide(layouts) => {
-						let mut result = Vec::new();
-						for lay in layouts{
-							result.append(&mut lay.get_widgets());
-						}
-						result
-					},
-					LayoutOperation::Trim{pixels: _pixels, first, second} => {
-						let mut result = first.get_widgets();
-						result.append(&mut second.get_widgets());
-						result
-					},
-				}
-			},
-			Layout::Group{span: _span, panel: _panel, content} => content.get_widgets(),
-			Layout::Control(widget) => {
-				let mut result = Vec::new();
-				result.push(widget);
-				result
-			}
-
-		}
-	}
-
-	pub fn get_panels(&self) -> Vec<&Panel> {
-		match self {
-			Layout::Operation{op, dir: _dir, sense: _sense} => {
-				match op {
-					LayoutOperation::Nop => Vec::new(),
+					LayoutOperation::Nop => (),
 					LayoutOperation::Split{proportion: _prop, first, second} => {
-						let mut result = first.get_panels();
-						result.append(&mut second.get_panels());
-						result
+						first.paint(surface);
+						second.paint(surface);
 					},
 					LayoutOperation::Divide(layouts) => {
-						let mut result = Vec::new();
 						for lay in layouts{
-							result.append(&mut lay.get_panels());
+							lay.paint(surface);
 						}
-						result
 					},
 					LayoutOperation::Trim{pixels: _pixels, first, second} => {
-						let mut result = first.get_panels();
-						result.append(&mut second.get_panels());
-						result
+						first.paint(surface);
+						second.paint(surface);
 					},
 				}
 			},
 			Layout::Group{span: _span, panel, content} => {
-				let mut result = Vec::new();
-				result.push(panel);
-				result.append(&mut content.get_panels());
-				result
+				panel.paint(surface);
+				let (x1, y1, x2, y2) = panel.get_space().prop_coords;
+				surface.push_clip(x1, y1, x2, y2);
+				content.paint(surface);
+				surface.pop_clip();
 			},
-			Layout::Control(_) => Vec::new(),
+			Layout::Control(widget) => widget.paint(surface),
 		}
 	}
 }
\ No newline at end of file
@@ -15,16 +15,26 @@ limitations under the License.
 */
 
 pub mod layout;
+pub mod accelerator;
 pub mod direction;
+pub mod navigation;
 pub mod screen;
+pub mod screen_stack;
 pub mod space;
 pub mod surface;
+pub mod text_layout;
+pub mod transform;
 pub mod window;
 
 pub use direction::{Direction, Sense};
+pub use accelerator::{Chord, Accelerators};
+pub use navigation::ArrowWrap;
 pub use space::Space;
 pub use screen::Screen;
+pub use screen_stack::ScreenStack;
 pub use layout::Layout;
-pub use window::{Window, UpdateData, MouseStatus, KeyboardStatus, ScreenId};
+pub use window::{Window, UpdateData, MouseStatus, KeyboardStatus, Key, Modifiers, ScreenId};
 pub use surface::Surface;
-pub use surface::ImageId;
\ No newline at end of file
+pub use surface::{ImageId, ImageError, ImageProvider};
+pub use transform::{Transform, TransformStack};
+pub use text_layout::{TextLayout, TextFormat, HorizontalAlign, VerticalAlign, TextWrap};
\ No newline at end of file
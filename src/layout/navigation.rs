@@ -0,0 +1,189 @@
+/*
+Copyright 2021 Gonzalo Fernández Hernández
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+use crate::{Direction, Sense, Widget};
+
+/// What happens when an arrow key is pressed and there is no widget in that direction
+///     * None: The focus stays
+///     * Horizontal: Left and Right go to the widget on the other side of the row
+///     * Vertical: Up and Down go to the widget on the other side of the column
+///     * Both: Every arrow wraps around
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ArrowWrap {
+    None,
+    Horizontal,
+    Vertical,
+    Both,
+}
+
+impl ArrowWrap {
+    fn wraps(&self, direction: Direction) -> bool {
+        matches!((self, direction),
+            (ArrowWrap::Both, _) |
+            (ArrowWrap::Horizontal, Direction::Horizontal) |
+            (ArrowWrap::Vertical, Direction::Vertical))
+    }
+}
+
+/// Extent of a widget along the moving axis and across it, in pixels, as (start, end)
+fn extents(widget: &Widget, direction: Direction) -> ((i64, i64), (i64, i64)) {
+    let (x1, y1, x2, y2) = widget.get_space().pixel_coords;
+    let horizontal = (x1 as i64, x2 as i64);
+    let vertical = (y1 as i64, y2 as i64);
+    match direction {
+        Direction::Horizontal => (horizontal, vertical),
+        Direction::Vertical => (vertical, horizontal),
+    }
+}
+
+/// Pixels between two ranges, 0 if they overlap
+fn gap(a: (i64, i64), b: (i64, i64)) -> i64 {
+    (b.0 - a.1).max(a.0 - b.1).max(0)
+}
+
+/// Whether two ranges share some pixels. Ranges that only touch do not.
+fn overlap(a: (i64, i64), b: (i64, i64)) -> bool {
+    a.1.min(b.1) > a.0.max(b.0)
+}
+
+/// Focusable widget nearest to the focused one in a direction.
+/// The candidates are the widgets whose center is beyond the center of the focused one.
+/// They are sorted by: overlapping the focused one across the axis, distance along the axis,
+/// distance between the centers across the axis and tree order.
+/// If there is none and the wrap allows it, the widget at the other end, sorted the same way, is chosen.
+/// params:
+///     * widgets: The widgets of the screen, in tree order
+///     * focus: The index of the focused widget
+///     * direction: The axis of the movement
+///     * sense: Forward is right or down, Backwards is left or up
+///     * wrap: Whether the movement wraps around
+/// returns:
+///     The index of the widget to focus, if any
+pub fn nearest_focus(widgets: &[&mut Widget], focus: usize, direction: Direction, sense: Sense, wrap: ArrowWrap) -> Option<usize> {
+    let forward = matches!(sense, Sense::Forward);
+    let (along, across) = extents(widgets[focus], direction);
+    let center = along.0 + along.1;
+    let center_across = across.0 + across.1;
+
+    let candidates = || widgets.iter().enumerate()
+        .filter(move |(i, w)| *i != focus && w.can_focus())
+        .map(|(i, w)| (i, extents(w, direction)));
+    let score = |(_, other_across): ((i64, i64), (i64, i64)), distance: i64| {
+        let apart = !overlap(across, other_across);
+        let offset = (other_across.0 + other_across.1 - center_across).abs();
+        (apart, distance, offset)
+    };
+
+    let nearest = candidates()
+        .filter(|(_, (other, _))| if forward { other.0 + other.1 > center } else { other.0 + other.1 < center })
+        .min_by_key(|(i, extent)| (score(*extent, gap(along, extent.0)), *i))
+        .map(|(i, _)| i);
+    if nearest.is_some() || !wrap.wraps(direction) {
+        return nearest;
+    }
+    // The farthest one in the opposite direction
+    candidates()
+        .min_by_key(|(i, extent)| {
+            let start = if forward { extent.0.0 } else { -extent.0.1 };
+            (score(*extent, start), *i)
+        })
+        .map(|(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Space, Theme};
+
+    /// A grid of 3 x 3 buttons of 100 pixels, in tree order by rows. The indices in labels are labels instead.
+    ///     0 1 2
+    ///     3 4 5
+    ///     6 7 8
+    fn grid(labels: &[usize]) -> Vec<Widget> {
+        let rows = Space::new(300, 300).divide(3, Direction::Vertical, Sense::Forward);
+        rows.into_iter()
+            .flat_map(|row| row.divide(3, Direction::Horizontal, Sense::Forward))
+            .enumerate()
+            .map(|(i, space)| {
+                let mut widget = if labels.contains(&i) {
+                    Widget::new_label("", None, Theme::new())
+                } else {
+                    Widget::new_button(0, Theme::new())
+                };
+                widget.set_space(space);
+                widget
+            })
+            .collect()
+    }
+
+    fn nearest(widgets: &mut [Widget], focus: usize, direction: Direction, sense: Sense, wrap: ArrowWrap) -> Option<usize> {
+        let widgets: Vec<&mut Widget> = widgets.iter_mut().collect();
+        nearest_focus(&widgets, focus, direction, sense, wrap)
+    }
+
+    #[test]
+    fn arrows_go_to_the_nearest_widget() {
+        let mut widgets = grid(&[]);
+        assert_eq!(nearest(&mut widgets, 4, Direction::Horizontal, Sense::Forward, ArrowWrap::None), Some(5));
+        assert_eq!(nearest(&mut widgets, 4, Direction::Horizontal, Sense::Backwards, ArrowWrap::None), Some(3));
+        assert_eq!(nearest(&mut widgets, 4, Direction::Vertical, Sense::Backwards, ArrowWrap::None), Some(1));
+        assert_eq!(nearest(&mut widgets, 4, Direction::Vertical, Sense::Forward, ArrowWrap::None), Some(7));
+        assert_eq!(nearest(&mut widgets, 0, Direction::Vertical, Sense::Forward, ArrowWrap::None), Some(3));
+    }
+
+    #[test]
+    fn ties_go_to_the_first_widget_in_tree_order() {
+        // A wide button over two narrow ones
+        let (top, bottom) = Space::new(200, 200).split(0.5_f32, Direction::Vertical, Sense::Forward);
+        let mut widgets: Vec<Widget> = std::iter::once(top)
+            .chain(bottom.divide(2, Direction::Horizontal, Sense::Forward))
+            .map(|space| {
+                let mut widget = Widget::new_button(0, Theme::new());
+                widget.set_space(space);
+                widget
+            })
+            .collect();
+        assert_eq!(nearest(&mut widgets, 0, Direction::Vertical, Sense::Forward, ArrowWrap::None), Some(1));
+        assert_eq!(nearest(&mut widgets, 2, Direction::Vertical, Sense::Backwards, ArrowWrap::None), Some(0));
+        // Both only touch the row, at the same distance
+        let mut widgets = grid(&[5]);
+        assert_eq!(nearest(&mut widgets, 4, Direction::Horizontal, Sense::Forward, ArrowWrap::None), Some(2));
+    }
+
+    #[test]
+    fn widgets_that_cannot_focus_are_skipped() {
+        let mut widgets = grid(&[1]);
+        // The same row is preferred to a nearer widget in other row
+        assert_eq!(nearest(&mut widgets, 0, Direction::Horizontal, Sense::Forward, ArrowWrap::None), Some(2));
+        assert_eq!(nearest(&mut widgets, 7, Direction::Vertical, Sense::Backwards, ArrowWrap::None), Some(4));
+        // Without a widget in the column, the nearest row wins
+        let mut widgets = grid(&[1, 4]);
+        assert_eq!(nearest(&mut widgets, 7, Direction::Vertical, Sense::Backwards, ArrowWrap::None), Some(3));
+    }
+
+    #[test]
+    fn arrows_wrap_only_in_their_mode() {
+        let mut widgets = grid(&[]);
+        assert_eq!(nearest(&mut widgets, 5, Direction::Horizontal, Sense::Forward, ArrowWrap::None), None);
+        assert_eq!(nearest(&mut widgets, 5, Direction::Horizontal, Sense::Forward, ArrowWrap::Vertical), None);
+        assert_eq!(nearest(&mut widgets, 5, Direction::Horizontal, Sense::Forward, ArrowWrap::Horizontal), Some(3));
+        assert_eq!(nearest(&mut widgets, 3, Direction::Horizontal, Sense::Backwards, ArrowWrap::Both), Some(5));
+        assert_eq!(nearest(&mut widgets, 7, Direction::Vertical, Sense::Forward, ArrowWrap::Horizontal), None);
+        assert_eq!(nearest(&mut widgets, 7, Direction::Vertical, Sense::Forward, ArrowWrap::Vertical), Some(1));
+        assert_eq!(nearest(&mut widgets, 1, Direction::Vertical, Sense::Backwards, ArrowWrap::Both), Some(7));
+    }
+}
@@ -14,29 +14,146 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
-use crate::{Surface, Space, UpdateData, Layout, Panel};
+use crate::{Surface, Space, UpdateData, MouseStatus, KeyboardStatus, Key, Layout, Widget, Event, Message};
+use crate::{Direction, Sense, ArrowWrap, Chord, Accelerators, CommandId};
+use crate::layout::navigation::nearest_focus;
 
+/// A screen of the window, with its widgets laid out
+///     * layout: The tree of spaces with the panels and widgets
+///     * modal: Whether the screen under it is painted when it is on top
+///     * focus: Index, in Layout tree order, of the widget with the keyboard focus
+///     * arrow_wrap: Whether the arrow keys wrap around when moving the focus
+///     * accelerators: The key chords that request commands while the screen is shown
 pub struct Screen {
     layout: Layout,
+    modal: bool,
+    focus: Option<usize>,
+    arrow_wrap: ArrowWrap,
+    accelerators: Accelerators,
 }
 
 impl Screen {
     pub fn new(init_function: fn(&mut Layout)) -> Self {
         let mut layout = Layout::new();
         init_function(&mut layout);
-        Screen{layout}
+        Screen{layout, modal: false, focus: None, arrow_wrap: ArrowWrap::None, accelerators: Accelerators::new()}
+    }
+
+    /// Creates a modal screen: when it is pushed, the screen under it is still painted, but dimmed.
+    pub fn new_modal(init_function: fn(&mut Layout)) -> Self {
+        let mut screen = Screen::new(init_function);
+        screen.modal = true;
+        screen
+    }
+
+    pub fn is_modal(&self) -> bool {
+        self.modal
+    }
+
+    /// Sets whether the arrow keys wrap around when there is no widget in their direction
+    pub fn set_arrow_wrap(&mut self, arrow_wrap: ArrowWrap) {
+        self.arrow_wrap = arrow_wrap;
+    }
+
+    /// Adds an accelerator that works while the screen is shown
+    /// params:
+    ///     * chords: The sequence of chords, usually only one, like Ctrl+S
+    ///     * command: The command sent with RequestCommand when the sequence is pressed
+    pub fn add_accelerator(&mut self, chords: &[Chord], command: CommandId) {
+        self.accelerators.add(chords, command);
+    }
+
+    /// Checks the accelerators of the screen with the key pressed in an update
+    pub fn process_accelerators(&mut self, data: &UpdateData) -> Option<CommandId> {
+        self.accelerators.process(data)
     }
 
     pub fn resize(&mut self, space: Space) {
         self.layout.resize(space);
     }
 
-    pub fn update(&mut self, data: &UpdateData) {
-        self.layout.get_widgets_mut().iter_mut().map(|x| x.update(data)).collect()
+    /// Updates the widgets and returns the events they produced.
+    /// Clicking a widget focuses it, Tab / Shift+Tab move the focus in Layout tree order,
+    /// and the arrows move it to the nearest widget in their direction, unless the focused widget uses them.
+    /// Only the focused widget receives the keyboard.
+    pub fn update(&mut self, data: &UpdateData) -> Vec<Event> {
+        let mut widgets = self.layout.get_widgets_mut();
+        let mut focus = self.focus.filter(|x| widgets[*x].can_focus());
+
+        if data.mouse_status == MouseStatus::Pressed {
+            let (x, y) = data.mouse_position;
+            focus = widgets.iter().position(|w| w.can_focus() && w.is_inside(x, y));
+        }
+        // The keys used by the screen are not received by the widgets
+        let mut used = false;
+        if let KeyboardStatus::KeyDown(key) = data.keyboard_status {
+            let arrow = match key {
+                Key::Left => Some((Direction::Horizontal, Sense::Backwards)),
+                Key::Right => Some((Direction::Horizontal, Sense::Forward)),
+                Key::Up => Some((Direction::Vertical, Sense::Backwards)),
+                Key::Down => Some((Direction::Vertical, Sense::Forward)),
+                _ => None,
+            };
+            if key == Key::Tab {
+                focus = next_focus(&widgets, focus, !data.modifiers.shift);
+                used = true;
+            } else if let Some((direction, sense)) = arrow {
+                match focus {
+                    Some(index) if widgets[index].captures_key(key) => (),
+                    Some(index) => {
+                        focus = nearest_focus(&widgets, index, direction, sense, self.arrow_wrap).or(focus);
+                        used = true;
+                    },
+                    None => {
+                        focus = next_focus(&widgets, None, true);
+                        used = true;
+                    },
+                }
+            }
+        }
+        set_focus(&mut widgets, self.focus, focus);
+        self.focus = focus;
+
+        let unfocused = data.without_keyboard();
+        let focused = if used { &unfocused } else { data };
+        widgets.iter_mut().enumerate()
+            .filter_map(|(i, w)| w.update(if Some(i) == focus { focused } else { &unfocused }))
+            .collect()
+    }
+
+    /// Applies a message from the app to the widgets of this screen
+    pub fn process_message(&mut self, message: &Message) {
+        self.accelerators.process_message(message);
+        self.layout.get_widgets_mut().iter_mut().for_each(|x| x.process_message(message));
     }
 
     pub fn paint(&self, surface: &mut dyn Surface) {
-        self.layout.get_panels().iter().map(|x| x.paint(surface)).for_each(drop);
-        self.layout.get_widgets().iter().map(|x| x.paint(surface)).collect()
+        self.layout.paint(surface);
+    }
+}
+
+/// Moves the focus from a widget to another
+fn set_focus(widgets: &mut [&mut Widget], old: Option<usize>, new: Option<usize>) {
+    if let Some(index) = old {
+        widgets[index].set_focus(false);
     }
-}
\ No newline at end of file
+    if let Some(index) = new {
+        widgets[index].set_focus(true);
+    }
+}
+
+/// Next focusable widget in tree order, or previous if forward is false. The order wraps around.
+fn next_focus(widgets: &[&mut Widget], focus: Option<usize>, forward: bool) -> Option<usize> {
+    let count = widgets.len();
+    if count == 0 {
+        return None;
+    }
+    let start = match (focus, forward) {
+        (Some(index), _) => index,
+        (None, true) => count - 1,
+        (None, false) => 0,
+    };
+    (1..=count)
+        .map(|i| if forward { (start + i) % count } else { (start + count - i) % count })
+        .find(|x| widgets[*x].can_focus())
+}
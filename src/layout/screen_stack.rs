@@ -0,0 +1,137 @@
+/*
+Copyright 2021 Gonzalo Fernández Hernández
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+use crate::{Screen, ScreenId, Surface, Space, UpdateData, Event, Message, Color, Chord, Accelerators, CommandId};
+
+/// Color painted over the screens that lay under a modal screen
+const DIM_COLOR: Color = Color {r: 0_f32, g: 0_f32, b: 0_f32, a: 0.5_f32};
+
+/// Screens of a window and the stack used to navigate between them.
+/// Only the screen on top of the stack gets the input. If that screen is modal,
+/// the screen under it is painted too (dimmed), and so on.
+/// The accelerators of the stack work in every screen.
+pub struct ScreenStack {
+    screens: Vec<Screen>,
+    stack: Vec<ScreenId>,
+    dimensions: (u32, u32),
+    accelerators: Accelerators,
+}
+
+impl ScreenStack {
+    /// Creates an empty stack for a window with the given dimensions
+    pub fn new(width: u32, height: u32) -> Self {
+        ScreenStack{screens: Vec::new(), stack: Vec::new(), dimensions: (width, height), accelerators: Accelerators::new()}
+    }
+
+    /// Adds a screen and returns its id. The first screen added is placed on the stack.
+    pub fn add_screen(&mut self, mut screen: Screen) -> ScreenId {
+        screen.resize(Space::new(self.dimensions.0, self.dimensions.1));
+        self.screens.push(screen);
+        let id = self.screens.len() - 1;
+        if self.stack.is_empty() {
+            self.stack.push(id);
+        }
+        id
+    }
+
+    /// Adds an accelerator that works in every screen
+    pub fn add_accelerator(&mut self, chords: &[Chord], command: CommandId) {
+        self.accelerators.add(chords, command);
+    }
+
+    /// Id of the screen on top of the stack
+    pub fn current(&self) -> Option<ScreenId> {
+        self.stack.last().copied()
+    }
+
+    /// Resizes the visible screens. The rest are resized when they become visible.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.dimensions = (width, height);
+        self.resize_visible();
+    }
+
+    /// Updates the screen on top of the stack and returns the events it produced.
+    /// A key that completes an accelerator, of the screen or of the stack, emits RequestCommand
+    /// and is not received by the widgets.
+    pub fn update(&mut self, data: &UpdateData) -> Vec<Event> {
+        let screen = match self.current() {
+            Some(id) => &mut self.screens[id],
+            None => return Vec::new(),
+        };
+        let accelerators = &mut self.accelerators;
+        match screen.process_accelerators(data).or_else(|| accelerators.process(data)) {
+            Some(command) => {
+                let mut events = vec![Event::RequestCommand(command)];
+                events.append(&mut screen.update(&data.without_keyboard()));
+                events
+            },
+            None => screen.update(data),
+        }
+    }
+
+    /// Applies a message from the app.
+    /// Navigation messages change the stack; the rest are sent to every screen.
+    ///     * ChangeScreen: Replaces the screen on top of the stack
+    ///     * PushScreen: Places a screen on top of the stack
+    ///     * PopScreen: Removes the screen on top of the stack (the last one is never removed)
+    ///     * ClearScreenStack: Removes every screen but the first one of the stack
+    pub fn process_message(&mut self, message: &Message) {
+        match message {
+            Message::ChangeScreen(id) if *id < self.screens.len() => {
+                self.stack.pop();
+                self.stack.push(*id);
+            },
+            Message::PushScreen(id) if *id < self.screens.len() => self.stack.push(*id),
+            Message::PopScreen() if self.stack.len() > 1 => { self.stack.pop(); },
+            Message::ClearScreenStack() => self.stack.truncate(1),
+            Message::ChangeScreen(_) | Message::PushScreen(_) | Message::PopScreen() => return,
+            _ => {
+                self.accelerators.process_message(message);
+                self.screens.iter_mut().for_each(|x| x.process_message(message));
+                return;
+            }
+        }
+        self.resize_visible();
+    }
+
+    /// Paints the visible screens, from the bottom to the top of the stack
+    pub fn paint(&self, surface: &mut dyn Surface) {
+        let first = self.first_visible();
+        for (i, id) in self.stack.iter().enumerate().skip(first) {
+            if i > first {
+                surface.draw_rectangle(-1_f32, 1_f32, 1_f32, -1_f32, None, Some(&DIM_COLOR));
+            }
+            self.screens[*id].paint(surface);
+        }
+    }
+
+    /// Position in the stack of the lowest screen that must be painted
+    fn first_visible(&self) -> usize {
+        let mut first = self.stack.len().saturating_sub(1);
+        while first > 0 && self.screens[self.stack[first]].is_modal() {
+            first -= 1;
+        }
+        first
+    }
+
+    fn resize_visible(&mut self) {
+        let first = self.first_visible();
+        for id in self.stack.iter().skip(first) {
+            self.screens[*id].resize(Space::new(self.dimensions.0, self.dimensions.1));
+        }
+    }
+}
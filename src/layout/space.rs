@@ -212,6 +212,11 @@ impl Space {
         result
     }
 
+    /// Maps a position in pixels of the window to proportional coordinates
+    pub fn to_prop(&self, x: f32, y: f32) -> (f32, f32) {
+        (x * 2_f32 / self.total.0 as f32 - 1_f32, 1_f32 - y * 2_f32 / self.total.1 as f32)
+    }
+
     pub fn shrink(&self, pixels_hor: u32, pixels_vert: u32) -> Self {
         let horiz_prop = ((pixels_hor << 1) as f32 / self.total.0 as f32).min((self.prop_coords.2 - self.prop_coords.0) * 0.5_f32);
         let vert_prop = ((pixels_vert << 1) as f32 / self.total.1 as f32).min((self.prop_coords.1 - self.prop_coords.3) * 0.5_f32);
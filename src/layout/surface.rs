@@ -14,19 +14,117 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
+use std::fmt;
+
 use crate::Color;
+use crate::{FontProvider, TextStyle};
 
 pub type ImageId = usize;
 
-pub trait Surface {
-    fn load_image(&mut self, location: &str) -> ImageId;
+/// Error loading an image
+///     * Io: The file could not be read
+///     * Decode: The data is not an image in a format that can be read
+///     * InvalidSize: The pixels given do not match the width and height
+///     * Texture: The image could not be stored to be drawn
+#[derive(Debug)]
+pub enum ImageError {
+    Io(std::io::Error),
+    Decode(image::ImageError),
+    InvalidSize,
+    Texture(String),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImageError::Io(error) => write!(f, "Cannot read image: {}", error),
+            ImageError::Decode(error) => write!(f, "Cannot decode image: {}", error),
+            ImageError::InvalidSize => write!(f, "The pixels do not match the size of the image"),
+            ImageError::Texture(error) => write!(f, "Cannot create texture: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+impl From<std::io::Error> for ImageError {
+    fn from(error: std::io::Error) -> Self {
+        ImageError::Io(error)
+    }
+}
+
+impl From<image::ImageError> for ImageError {
+    fn from(error: image::ImageError) -> Self {
+        ImageError::Decode(error)
+    }
+}
+
+/// Loads images to be drawn. The ids are valid as long as the provider lives.
+pub trait ImageProvider {
+    /// Loads an image from a file, in any format the image crate can read, and returns its id
+    fn load_image(&mut self, location: &str) -> Result<ImageId, ImageError> {
+        let bytes = std::fs::read(location)?;
+        self.load_image_from_bytes(&bytes)
+    }
+
+    /// Loads an image from the content of a file, in any format the image crate can read, and returns its id
+    fn load_image_from_bytes(&mut self, bytes: &[u8]) -> Result<ImageId, ImageError> {
+        let image = image::load_from_memory(bytes)?.to_rgba8();
+        let (width, height) = image.dimensions();
+        self.load_image_from_rgba(width, height, image.into_raw())
+    }
+
+    /// Loads an image from its pixels, in rows from the top, 4 bytes (RGBA) each, and returns its id
+    fn load_image_from_rgba(&mut self, width: u32, height: u32, pixels: Vec<u8>) -> Result<ImageId, ImageError>;
+
+    /// Dimensions in pixels of an image, or None if no image was loaded with that id
+    fn image_size(&self, img: ImageId) -> Option<(u32, u32)>;
+}
+
+pub trait Surface: FontProvider + ImageProvider {
     fn clear(&mut self, color: &Color);
     fn draw_line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, line_color: &Color);
     fn draw_rectangle(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, border_color: Option<&Color>, fill_color: Option<&Color>);
     fn draw_triangle(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x3: f32, y3: f32, border_color: Option<&Color>, fill_color: Option<&Color>);
     fn draw_ellipse(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, border_color: Option<&Color>, fill_color: Option<&Color>);
+    /// Draws a circle around a center (x, y). The radius is measured horizontally, and the vertical
+    /// radius is scaled by the aspect of the surface, so the circle is round in pixels.
     fn draw_circle(&mut self, x: f32, y: f32, radius: f32, border_color: Option<&Color>, fill_color: Option<&Color>);
     fn draw_rounded_rectangle(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, rad_pixel: u32, border_color: Option<&Color>, fill_color: Option<&Color>);
-    fn draw_text(&mut self, text: &str, x1: f32, y1: f32, x2: f32, y2: f32, text_color: &Color);
-    fn draw_image(&mut self, img: ImageId, x1: f32, y1: f32, x2: f32, y2: f32);
+    /// Draws a line of text from the top left corner of the box. Whatever falls out of the box is not drawn.
+    // The box is given as four coordinates, like in the other draw_ methods
+    #[allow(clippy::too_many_arguments)]
+    fn draw_text(&mut self, text: &str, x1: f32, y1: f32, x2: f32, y2: f32, text_color: &Color, style: &TextStyle);
+
+    /// Makes everything drawn until the matching pop_opacity more transparent, to fade panels or screens.
+    /// The opacities pushed are multiplied: 0 is invisible and 1 leaves the colors as they are.
+    fn push_opacity(&mut self, opacity: f32);
+    /// Restores the opacity there was before the last push_opacity
+    fn pop_opacity(&mut self);
+    /// Restricts everything drawn until the matching pop_clip to a rectangle.
+    /// The rectangles pushed are intersected, so nothing is drawn outside any of them.
+    /// The rectangle is transformed too; when it is rotated, the rectangle that contains it is used.
+    fn push_clip(&mut self, x1: f32, y1: f32, x2: f32, y2: f32);
+    /// Restores the clipping there was before the last push_clip
+    fn pop_clip(&mut self);
+
+    /// Saves the transform applied to what is drawn, to be restored by the matching pop_transform
+    fn push_transform(&mut self);
+    /// Restores the transform saved by the last push_transform
+    fn pop_transform(&mut self);
+    /// Moves everything drawn next
+    fn translate(&mut self, x: f32, y: f32);
+    /// Scales everything drawn next around the origin
+    fn scale(&mut self, x: f32, y: f32);
+    /// Rotates everything drawn next around the origin, counterclockwise, by an angle in radians
+    fn rotate(&mut self, angle: f32);
+
+    /// Draws an image stretched to the box
+    fn draw_image(&mut self, img: ImageId, x1: f32, y1: f32, x2: f32, y2: f32) {
+        self.draw_image_region(img, x1, y1, x2, y2, (0_f32, 0_f32, 1_f32, 1_f32));
+    }
+
+    /// Draws a part of an image stretched to the box.
+    /// The region (u1, v1, u2, v2) is given in proportions of the image: its top left corner is (0, 0) and its bottom right (1, 1).
+    fn draw_image_region(&mut self, img: ImageId, x1: f32, y1: f32, x2: f32, y2: f32, region: (f32, f32, f32, f32));
 }
\ No newline at end of file
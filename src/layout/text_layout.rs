@@ -0,0 +1,370 @@
+/*
+Copyright 2021 Gonzalo Fernández Hernández
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+use std::ops::Range;
+
+use crate::{Surface, Color, TextStyle, FontProvider};
+
+const ELLIPSIS: &str = "\u{2026}";
+
+/// Horizontal position of the lines in the box
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HorizontalAlign {
+    Left,
+    Center,
+    Right,
+}
+
+/// Vertical position of the block of lines in the box
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VerticalAlign {
+    Top,
+    Middle,
+    Bottom,
+}
+
+/// How the lines are broken when they are wider than the box
+///     * NoWrap: Lines are only broken at '\n'
+///     * Word: Lines are broken between words. A word wider than the box is broken between characters.
+///     * Character: Lines are broken between any characters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextWrap {
+    NoWrap,
+    Word,
+    Character,
+}
+
+/// How a text is fitted into a box
+///     * style: Font, size and weight
+///     * horizontal, vertical: Alignment in the box
+///     * wrap: How the lines are broken
+///     * line_spacing: Distance between lines, in heights of a line (1.0 for no extra space)
+///     * ellipsis: Whether the text that does not fit is cut with '…'
+#[derive(Debug, Clone, Copy)]
+pub struct TextFormat {
+    pub style: TextStyle,
+    pub horizontal: HorizontalAlign,
+    pub vertical: VerticalAlign,
+    pub wrap: TextWrap,
+    pub line_spacing: f32,
+    pub ellipsis: bool,
+}
+
+impl TextFormat {
+    /// Creates a format for a single line in the left middle of the box, with ellipsis
+    pub fn new(style: TextStyle) -> Self {
+        TextFormat{
+            style,
+            horizontal: HorizontalAlign::Left,
+            vertical: VerticalAlign::Middle,
+            wrap: TextWrap::NoWrap,
+            line_spacing: 1_f32,
+            ellipsis: true,
+        }
+    }
+}
+
+impl Default for TextFormat {
+    fn default() -> Self {
+        TextFormat::new(TextStyle::default())
+    }
+}
+
+/// Position of a character in a line, in pixels from the left of the box
+///     * index: Byte index of the character in the text
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphPosition {
+    pub index: usize,
+    pub x: f32,
+    pub width: f32,
+}
+
+/// A line of laid out text. Positions are in pixels from the top left corner of the box.
+///     * text: What is drawn (it can end with '…')
+///     * range: Bytes of the original text shown in the line
+///     * glyphs: Position of every character of the range
+#[derive(Debug, Clone)]
+pub struct TextLine {
+    pub text: String,
+    pub range: Range<usize>,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub glyphs: Vec<GlyphPosition>,
+}
+
+/// A text broken into lines and placed into a box of a given size in pixels.
+/// Widgets share it, so text is drawn and hit tested the same way everywhere.
+#[derive(Debug, Clone)]
+pub struct TextLayout {
+    pub lines: Vec<TextLine>,
+    pub width: f32,
+    pub height: f32,
+    pub line_height: f32,
+    style: TextStyle,
+}
+
+impl TextLayout {
+    /// Lays out a text into a box
+    /// params:
+    ///     * text: The text, '\n' breaks a line
+    ///     * width, height: Size of the box in pixels
+    ///     * format: How the text fits the box
+    ///     * fonts: Used to measure the text
+    pub fn new<F: FontProvider + ?Sized>(text: &str, width: f32, height: f32, format: &TextFormat, fonts: &F) -> Self {
+        let style = format.style;
+        let measure = |x: &str| fonts.measure_text(x, &style).width;
+        let line_height = fonts.measure_text("", &style).height;
+        let pitch = line_height * format.line_spacing;
+
+        let mut ranges = Vec::new();
+        let mut offset = 0;
+        for paragraph in text.split('\n') {
+            for range in TextLayout::break_paragraph(paragraph, width, format.wrap, &measure) {
+                ranges.push(offset + range.start .. offset + range.end);
+            }
+            offset += paragraph.len() + 1;
+        }
+
+        let mut truncated = false;
+        let max_lines = if pitch > 0_f32 {
+            (((height - line_height) / pitch).floor() as usize + 1).max(1)
+        } else {
+            ranges.len()
+        };
+        if format.ellipsis && ranges.len() > max_lines {
+            ranges.truncate(max_lines);
+            truncated = true;
+        }
+
+        let last = ranges.len().saturating_sub(1);
+        let mut lines: Vec<TextLine> = ranges.into_iter().enumerate().map(|(i, range)| {
+            let content = &text[range.clone()];
+            let cut = format.ellipsis && ((truncated && i == last) || measure(content) > width);
+            let (range, shown) = if cut {
+                truncated = true;
+                let mut end = range.end;
+                while end > range.start && measure(&format!("{}{}", text[range.start..end].trim_end(), ELLIPSIS)) > width {
+                    end = text[range.start..end].char_indices().last().map(|(x, _)| range.start + x).unwrap_or(range.start);
+                }
+                let kept = text[range.start..end].trim_end();
+                (range.start .. range.start + kept.len(), format!("{}{}", kept, ELLIPSIS))
+            } else {
+                (range, content.to_string())
+            };
+            let glyphs = text[range.clone()].char_indices().map(|(x, c)| {
+                let start = measure(&text[range.start .. range.start + x]);
+                GlyphPosition{index: range.start + x, x: start, width: measure(&text[range.start .. range.start + x + c.len_utf8()]) - start}
+            }).collect();
+            TextLine{width: measure(&shown), text: shown, range, x: 0_f32, y: i as f32 * pitch, glyphs}
+        }).collect();
+
+        let block_height = if lines.is_empty() { 0_f32 } else { (lines.len() - 1) as f32 * pitch + line_height };
+        let top = match format.vertical {
+            VerticalAlign::Top => 0_f32,
+            VerticalAlign::Middle => (height - block_height) * 0.5_f32,
+            VerticalAlign::Bottom => height - block_height,
+        };
+        for line in lines.iter_mut() {
+            let left = match format.horizontal {
+                HorizontalAlign::Left => 0_f32,
+                HorizontalAlign::Center => (width - line.width) * 0.5_f32,
+                HorizontalAlign::Right => width - line.width,
+            };
+            line.x = left;
+            line.y += top;
+            line.glyphs.iter_mut().for_each(|x| x.x += left);
+        }
+
+        TextLayout{lines, width, height, line_height, style}
+    }
+
+    /// Breaks a paragraph (text without '\n') into lines that fit a width
+    fn break_paragraph(text: &str, width: f32, wrap: TextWrap, measure: &dyn Fn(&str) -> f32) -> Vec<Range<usize>> {
+        let mut lines = Vec::new();
+        let mut start = 0;
+        let mut word_break = None;
+        if wrap != TextWrap::NoWrap {
+            for (i, c) in text.char_indices() {
+                let end = i + c.len_utf8();
+                if c == ' ' {
+                    word_break = Some(end);
+                    continue;
+                }
+                if i == start || measure(&text[start..end]) <= width {
+                    continue;
+                }
+                match word_break {
+                    Some(position) if wrap == TextWrap::Word && position > start => {
+                        lines.push(start..position);
+                        start = position;
+                        if measure(&text[start..end]) > width && i > start {
+                            lines.push(start..i);
+                            start = i;
+                        }
+                    },
+                    _ => {
+                        lines.push(start..i);
+                        start = i;
+                    }
+                }
+                word_break = None;
+            }
+        }
+        lines.push(start..text.len());
+        if wrap == TextWrap::NoWrap {
+            return lines;
+        }
+        lines.into_iter().map(|x| x.start .. x.start + text[x].trim_end().len()).collect()
+    }
+
+    /// Byte index of the text nearest to a point in pixels from the top left corner of the box
+    pub fn hit_test(&self, x: f32, y: f32) -> usize {
+        let line = match self.lines.iter().rev().find(|line| y >= line.y).or_else(|| self.lines.first()) {
+            Some(line) => line,
+            None => return 0,
+        };
+        line.glyphs.iter()
+            .find(|glyph| x < glyph.x + glyph.width * 0.5_f32)
+            .map(|glyph| glyph.index)
+            .unwrap_or(line.range.end)
+    }
+
+    /// Position in pixels (x, top of the line) of the caret placed before a byte index of the text
+    pub fn caret_position(&self, index: usize) -> (f32, f32) {
+        let line = match self.lines.iter().rev().find(|line| index >= line.range.start).or_else(|| self.lines.first()) {
+            Some(line) => line,
+            None => return (0_f32, 0_f32),
+        };
+        let x = line.glyphs.iter()
+            .find(|glyph| glyph.index >= index)
+            .map(|glyph| glyph.x)
+            .unwrap_or_else(|| line.glyphs.last().map(|x| x.x + x.width).unwrap_or(line.x));
+        (x, line.y)
+    }
+
+    /// Draws the lines into the box (x1, y1, x2, y2). The box must have the size the text was laid out for.
+    pub fn draw(&self, surface: &mut dyn Surface, x1: f32, y1: f32, x2: f32, y2: f32, color: &Color) {
+        if self.width <= 0_f32 || self.height <= 0_f32 {
+            return;
+        }
+        let scale_x = (x2 - x1) / self.width;
+        let scale_y = (y2 - y1) / self.height;
+        for line in self.lines.iter() {
+            surface.draw_text(&line.text, x1 + line.x * scale_x, y1 + line.y * scale_y, x2, y2, color, &self.style);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FontError, FontId, TextMetrics};
+
+    /// Every character is 10 pixels wide and lines are 20 pixels high
+    struct FixedFont;
+
+    impl FontProvider for FixedFont {
+        fn load_font_from_bytes(&mut self, _bytes: Vec<u8>) -> Result<FontId, FontError> {
+            Err(FontError::InvalidFont)
+        }
+
+        fn measure_text(&self, text: &str, _style: &TextStyle) -> TextMetrics {
+            TextMetrics{width: text.chars().count() as f32 * 10_f32, height: 20_f32, baseline: 16_f32}
+        }
+    }
+
+    fn format(wrap: TextWrap, ellipsis: bool) -> TextFormat {
+        TextFormat{
+            horizontal: HorizontalAlign::Left,
+            vertical: VerticalAlign::Top,
+            wrap,
+            ellipsis,
+            ..TextFormat::default()
+        }
+    }
+
+    fn lines(layout: &TextLayout) -> Vec<&str> {
+        layout.lines.iter().map(|x| x.text.as_str()).collect()
+    }
+
+    #[test]
+    fn word_wrap_breaks_between_words() {
+        let layout = TextLayout::new("hello world foo", 60_f32, 100_f32, &format(TextWrap::Word, false), &FixedFont);
+        assert_eq!(lines(&layout), vec!["hello", "world", "foo"]);
+        assert_eq!(layout.lines[1].range, 6..11);
+        assert_eq!(layout.lines[2].y, 40_f32);
+    }
+
+    #[test]
+    fn word_wrap_breaks_long_words_between_characters() {
+        let layout = TextLayout::new("abcdefgh", 30_f32, 100_f32, &format(TextWrap::Word, false), &FixedFont);
+        assert_eq!(lines(&layout), vec!["abc", "def", "gh"]);
+    }
+
+    #[test]
+    fn character_wrap_ignores_words() {
+        let layout = TextLayout::new("ab cd", 30_f32, 100_f32, &format(TextWrap::Character, false), &FixedFont);
+        assert_eq!(lines(&layout), vec!["ab", "cd"]);
+    }
+
+    #[test]
+    fn no_wrap_only_breaks_at_new_lines() {
+        let layout = TextLayout::new("hello world\nfoo", 30_f32, 100_f32, &format(TextWrap::NoWrap, false), &FixedFont);
+        assert_eq!(lines(&layout), vec!["hello world", "foo"]);
+        assert_eq!(layout.lines[1].range, 12..15);
+    }
+
+    #[test]
+    fn ellipsis_cuts_wide_lines() {
+        let layout = TextLayout::new("hello world", 60_f32, 100_f32, &format(TextWrap::NoWrap, true), &FixedFont);
+        assert_eq!(lines(&layout), vec!["hello\u{2026}"]);
+        assert_eq!(layout.lines[0].range, 0..5);
+    }
+
+    #[test]
+    fn ellipsis_ends_the_last_line_that_fits() {
+        let layout = TextLayout::new("a\nb\nc", 100_f32, 40_f32, &format(TextWrap::NoWrap, true), &FixedFont);
+        assert_eq!(lines(&layout), vec!["a", "b\u{2026}"]);
+    }
+
+    #[test]
+    fn lines_are_aligned_in_the_box() {
+        let mut centered = format(TextWrap::NoWrap, false);
+        centered.horizontal = HorizontalAlign::Center;
+        centered.vertical = VerticalAlign::Middle;
+        let layout = TextLayout::new("abc", 100_f32, 100_f32, &centered, &FixedFont);
+        assert_eq!((layout.lines[0].x, layout.lines[0].y), (35_f32, 40_f32));
+        assert_eq!(layout.lines[0].glyphs[0].x, 35_f32);
+
+        let mut right = format(TextWrap::NoWrap, false);
+        right.horizontal = HorizontalAlign::Right;
+        right.vertical = VerticalAlign::Bottom;
+        let layout = TextLayout::new("abc", 100_f32, 100_f32, &right, &FixedFont);
+        assert_eq!((layout.lines[0].x, layout.lines[0].y), (70_f32, 80_f32));
+    }
+
+    #[test]
+    fn hit_test_and_caret_position_agree() {
+        let layout = TextLayout::new("abc\ndef", 100_f32, 100_f32, &format(TextWrap::NoWrap, false), &FixedFont);
+        assert_eq!(layout.hit_test(14_f32, 0_f32), 1);
+        assert_eq!(layout.hit_test(100_f32, 0_f32), 3);
+        assert_eq!(layout.hit_test(0_f32, 25_f32), 4);
+        assert_eq!(layout.caret_position(1), (10_f32, 0_f32));
+        assert_eq!(layout.caret_position(7), (30_f32, 20_f32));
+    }
+}
@@ -0,0 +1,199 @@
+/*
+Copyright 2021 Gonzalo Fernández Hernández
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+/// 2D affine transform of the coordinates given to a Surface.
+/// A point (x, y) becomes (a * x + c * y + e, b * x + d * y + f).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Transform {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::identity()
+    }
+}
+
+impl Transform {
+    /// Transform that leaves the points as they are
+    pub fn identity() -> Self {
+        Transform{a: 1_f32, b: 0_f32, c: 0_f32, d: 1_f32, e: 0_f32, f: 0_f32}
+    }
+
+    pub fn translation(x: f32, y: f32) -> Self {
+        Transform{e: x, f: y, ..Transform::identity()}
+    }
+
+    pub fn scaling(x: f32, y: f32) -> Self {
+        Transform{a: x, d: y, ..Transform::identity()}
+    }
+
+    /// Rotation around the origin, counterclockwise
+    /// params:
+    ///     * angle: The angle in radians
+    ///     * aspect: Width divided by height of the surface, so shapes keep their proportions in pixels
+    pub fn rotation(angle: f32, aspect: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Transform{a: cos, b: sin * aspect, c: -sin / aspect, d: cos, ..Transform::identity()}
+    }
+
+    /// Transform that applies other first and then this one
+    pub fn multiply(&self, other: &Transform) -> Transform {
+        Transform{
+            a: self.a * other.a + self.c * other.b,
+            b: self.b * other.a + self.d * other.b,
+            c: self.a * other.c + self.c * other.d,
+            d: self.b * other.c + self.d * other.d,
+            e: self.a * other.e + self.c * other.f + self.e,
+            f: self.b * other.e + self.d * other.f + self.f,
+        }
+    }
+
+    /// Transform that undoes this one, or None if this one collapses the points into a line
+    pub fn inverse(&self) -> Option<Transform> {
+        let det = self.a * self.d - self.b * self.c;
+        if det.abs() < f32::EPSILON {
+            return None;
+        }
+        Some(Transform{
+            a: self.d / det,
+            b: -self.b / det,
+            c: -self.c / det,
+            d: self.a / det,
+            e: (self.c * self.f - self.d * self.e) / det,
+            f: (self.b * self.e - self.a * self.f) / det,
+        })
+    }
+
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        (self.a * x + self.c * y + self.e, self.b * x + self.d * y + self.f)
+    }
+
+    /// Smallest rectangle, as (x1, y1, x2, y2), that contains a rectangle transformed
+    pub fn apply_bounds(&self, x1: f32, y1: f32, x2: f32, y2: f32) -> (f32, f32, f32, f32) {
+        let points = [self.apply(x1, y1), self.apply(x2, y1), self.apply(x2, y2), self.apply(x1, y2)];
+        let left = points.iter().map(|p| p.0).fold(f32::MAX, f32::min);
+        let right = points.iter().map(|p| p.0).fold(f32::MIN, f32::max);
+        let bottom = points.iter().map(|p| p.1).fold(f32::MAX, f32::min);
+        let top = points.iter().map(|p| p.1).fold(f32::MIN, f32::max);
+        (left, top, right, bottom)
+    }
+}
+
+/// Transforms saved by a Surface. The last one is applied to everything drawn,
+/// and translate, scale and rotate change it in the coordinates it already has.
+#[derive(Debug, Clone)]
+pub struct TransformStack {
+    stack: Vec<Transform>,
+}
+
+impl Default for TransformStack {
+    fn default() -> Self {
+        TransformStack::new()
+    }
+}
+
+impl TransformStack {
+    pub fn new() -> Self {
+        TransformStack{stack: vec![Transform::identity()]}
+    }
+
+    /// The transform applied to what is drawn
+    pub fn current(&self) -> &Transform {
+        self.stack.last().unwrap()
+    }
+
+    /// Saves the current transform, so it is restored by pop
+    pub fn push(&mut self) {
+        let current = *self.current();
+        self.stack.push(current);
+    }
+
+    /// Restores the transform saved by the last push. The first transform is never removed.
+    pub fn pop(&mut self) {
+        if self.stack.len() > 1 {
+            self.stack.pop();
+        }
+    }
+
+    pub fn translate(&mut self, x: f32, y: f32) {
+        self.apply(&Transform::translation(x, y));
+    }
+
+    pub fn scale(&mut self, x: f32, y: f32) {
+        self.apply(&Transform::scaling(x, y));
+    }
+
+    pub fn rotate(&mut self, angle: f32, aspect: f32) {
+        self.apply(&Transform::rotation(angle, aspect));
+    }
+
+    /// Applies a transform before the current one
+    fn apply(&mut self, transform: &Transform) {
+        let current = self.stack.last_mut().unwrap();
+        *current = current.multiply(transform);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(point: (f32, f32), expected: (f32, f32)) {
+        assert!((point.0 - expected.0).abs() < 1e-5 && (point.1 - expected.1).abs() < 1e-5, "{:?} != {:?}", point, expected);
+    }
+
+    #[test]
+    fn inverse_gives_the_point_back() {
+        let transform = Transform::translation(0.25_f32, -0.5_f32)
+            .multiply(&Transform::rotation(0.7_f32, 1.5_f32))
+            .multiply(&Transform::scaling(2_f32, 0.5_f32));
+        let inverse = transform.inverse().unwrap();
+        let (x, y) = transform.apply(0.3_f32, -0.8_f32);
+        assert_near(inverse.apply(x, y), (0.3_f32, -0.8_f32));
+        assert!(Transform::scaling(0_f32, 1_f32).inverse().is_none());
+    }
+
+    #[test]
+    fn multiply_applies_the_other_first() {
+        let translate = Transform::translation(1_f32, 0_f32);
+        let scale = Transform::scaling(2_f32, 2_f32);
+        // Scaled and then translated
+        assert_near(translate.multiply(&scale).apply(1_f32, 1_f32), (3_f32, 2_f32));
+        // Translated and then scaled
+        assert_near(scale.multiply(&translate).apply(1_f32, 1_f32), (4_f32, 2_f32));
+    }
+
+    #[test]
+    fn pop_restores_the_pushed_transform() {
+        let mut stack = TransformStack::new();
+        stack.translate(0.5_f32, 0_f32);
+        stack.push();
+        stack.scale(2_f32, 2_f32);
+        // The scale happens in the translated coordinates
+        assert_near(stack.current().apply(1_f32, 1_f32), (2.5_f32, 2_f32));
+        stack.pop();
+        assert_near(stack.current().apply(1_f32, 1_f32), (1.5_f32, 1_f32));
+        // The first transform stays
+        stack.pop();
+        assert_near(stack.current().apply(1_f32, 1_f32), (1.5_f32, 1_f32));
+    }
+}
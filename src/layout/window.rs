@@ -14,23 +14,178 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
+use std::collections::HashSet;
+
 use crate::Screen;
+use crate::App;
+use crate::{Chord, CommandId};
 pub use usize as ScreenId;
 
+/// Status of the main mouse button in an update
+///     * Idle: The button has not changed since the last update
+///     * Pressed: The button has just been pressed
+///     * Released: The button has just been released
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum MouseStatus {
     Idle,
-    Clicked
+    Pressed,
+    Released,
+}
+
+/// Keys of the keyboard, independent of the technology of the window.
+/// Letters and digits are the keys, not the characters typed: use UpdateData::text for those.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Key {
+    A, B, C, D, E, F, G, H, I, J, K, L, M,
+    N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+    Num0, Num1, Num2, Num3, Num4, Num5, Num6, Num7, Num8, Num9,
+    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Insert,
+    Backspace,
+    Delete,
+    Enter,
+    Escape,
+    Tab,
+    Space,
+    Shift,
+    Ctrl,
+    Alt,
+    Logo,
+    Other,
 }
 
+impl std::fmt::Display for Key {
+    /// Name of the key as shown to the user, like "F5" or "PgUp"
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            Key::Num0 => "0",
+            Key::Num1 => "1",
+            Key::Num2 => "2",
+            Key::Num3 => "3",
+            Key::Num4 => "4",
+            Key::Num5 => "5",
+            Key::Num6 => "6",
+            Key::Num7 => "7",
+            Key::Num8 => "8",
+            Key::Num9 => "9",
+            Key::PageUp => "PgUp",
+            Key::PageDown => "PgDn",
+            Key::Insert => "Ins",
+            Key::Delete => "Del",
+            Key::Escape => "Esc",
+            Key::Backspace => "Backspace",
+            key => return write!(f, "{:?}", key),
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Status of the keyboard in an update
+///     * Idle: No key has changed since the last update
+///     * KeyDown: A key has just been pressed, or repeated while held (see UpdateData::key_repeat)
+///     * KeyUp: A key has just been released
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum KeyboardStatus {
     Idle,
-    KeyDown(u8),
-    KeyUp(u8),
+    KeyDown(Key),
+    KeyUp(Key),
+}
+
+/// Modifier keys held down
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub logo: bool,
 }
 
+/// Input given to the screens in an update
+///     * mouse_position: Position of the mouse in pixels
+///     * mouse_status: Change of the main mouse button
+///     * wheel: Lines scrolled with the mouse wheel since the last update; positive is up
+///     * keyboard_status: Change of the keyboard
+///     * key_repeat: Whether the KeyDown comes from a key held down, instead of a new press
+///     * modifiers: Modifier keys held down
+///     * text: Characters typed since the last update, already composed (dead keys, IME)
+///     * keys_held: Scancodes of the keys pressed and not released yet
 pub struct UpdateData {
     pub mouse_position : (u32, u32),
-    pub mouse_status: MouseStatus
+    pub mouse_status: MouseStatus,
+    pub wheel: f32,
+    pub keyboard_status: KeyboardStatus,
+    pub key_repeat: bool,
+    pub modifiers: Modifiers,
+    pub text: String,
+    keys_held: HashSet<u32>,
+}
+
+impl UpdateData {
+    /// Creates the input of a window with no mouse or keyboard activity
+    pub fn new() -> Self {
+        UpdateData{
+            mouse_position: (0_u32, 0_u32),
+            mouse_status: MouseStatus::Idle,
+            wheel: 0_f32,
+            keyboard_status: KeyboardStatus::Idle,
+            key_repeat: false,
+            modifiers: Modifiers::default(),
+            text: String::new(),
+            keys_held: HashSet::new(),
+        }
+    }
+
+    /// Copy of the input without the keyboard, for the widgets that do not have the focus
+    pub fn without_keyboard(&self) -> Self {
+        UpdateData{
+            mouse_position: self.mouse_position,
+            mouse_status: self.mouse_status,
+            wheel: self.wheel,
+            keyboard_status: KeyboardStatus::Idle,
+            key_repeat: false,
+            modifiers: self.modifiers,
+            text: String::new(),
+            keys_held: HashSet::new(),
+        }
+    }
+
+    /// Forgets the changes, once they have been given to the screens
+    pub fn reset(&mut self) {
+        self.mouse_status = MouseStatus::Idle;
+        self.wheel = 0_f32;
+        self.keyboard_status = KeyboardStatus::Idle;
+        self.key_repeat = false;
+        self.text.clear();
+    }
+
+    /// Records a key pressed. A key pressed again before it is released is a repetition.
+    /// params:
+    ///     * key: The key for the widgets
+    ///     * scancode: The physical key, which tells the repetitions apart
+    pub fn press_key(&mut self, key: Key, scancode: u32) {
+        self.key_repeat = !self.keys_held.insert(scancode);
+        self.keyboard_status = KeyboardStatus::KeyDown(key);
+    }
+
+    /// Records a key released
+    pub fn release_key(&mut self, key: Key, scancode: u32) {
+        self.keys_held.remove(&scancode);
+        self.keyboard_status = KeyboardStatus::KeyUp(key);
+    }
+
+    /// Forgets the keys and modifiers held when the window loses the focus, as their releases happen in other window
+    pub fn lose_focus(&mut self) {
+        self.keys_held.clear();
+        self.modifiers = Modifiers::default();
+    }
 }
 
 /// A window contains screens. 
@@ -45,12 +200,63 @@ pub trait Window {
     //fn new (caption: &str, width: u32, height: u32, resizable: bool) -> Self;
 
     /// Adds a Screen to the window and returns its id.
+    /// The first screen added is the one shown when the window starts.
     /// params:
     ///     * screen:   The screen to be added
     /// returns:
     ///     The id of the screen.
     fn add_screen(&mut self, screen: Screen) -> ScreenId;
 
-    /// Window loop
-    fn execute(self);
-}
\ No newline at end of file
+    /// Adds an accelerator that works in every screen.
+    /// The accelerators of the screen shown are checked first.
+    /// params:
+    ///     * chords: The sequence of chords, usually only one, like Alt+F4
+    ///     * command: The command sent with RequestCommand when the sequence is pressed
+    fn add_accelerator(&mut self, chords: &[Chord], command: CommandId);
+
+    /// Window loop.
+    /// Every Event produced by the screens is sent to the app, and the Messages it returns are applied.
+    /// params:
+    ///     * app: The application that handles the events
+    fn execute(self, app: Box<dyn App>);
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_key_pressed_again_is_repeated() {
+        let mut data = UpdateData::new();
+        data.press_key(Key::A, 30);
+        assert_eq!(data.keyboard_status, KeyboardStatus::KeyDown(Key::A));
+        assert!(!data.key_repeat);
+        data.reset();
+        data.press_key(Key::A, 30);
+        assert!(data.key_repeat);
+        data.reset();
+        data.press_key(Key::B, 48);
+        assert!(!data.key_repeat);
+    }
+
+    #[test]
+    fn a_key_released_is_pressed_anew() {
+        let mut data = UpdateData::new();
+        data.press_key(Key::A, 30);
+        data.release_key(Key::A, 30);
+        assert_eq!(data.keyboard_status, KeyboardStatus::KeyUp(Key::A));
+        data.press_key(Key::A, 30);
+        assert!(!data.key_repeat);
+    }
+
+    #[test]
+    fn losing_the_focus_releases_keys_and_modifiers() {
+        let mut data = UpdateData::new();
+        data.modifiers.ctrl = true;
+        data.modifiers.shift = true;
+        data.press_key(Key::S, 31);
+        data.lose_focus();
+        assert_eq!(data.modifiers, Modifiers::default());
+        data.press_key(Key::S, 31);
+        assert!(!data.key_repeat);
+    }
+}
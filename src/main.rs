@@ -23,6 +23,9 @@ mod style;
 mod messaging;
 
 mod gl;
+// The headless backend is only driven by the tests
+#[cfg_attr(not(test), allow(dead_code, unused_imports))]
+mod soft;
 
 use layout::*;
 use widget::*;
@@ -0,0 +1,30 @@
+/*
+Copyright 2021 Gonzalo Fernández Hernández
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+use crate::Event;
+use crate::Message;
+
+/// The application that runs on top of a Window.
+/// The window loop sends every Event produced by the UI to the app,
+/// and applies to the UI the Messages the app returns.
+pub trait App {
+    /// Processes an event from the UI
+    /// params:
+    ///     * event: The event produced by the UI
+    /// returns:
+    ///     The messages that must be applied to the UI (it can be empty)
+    fn handle_event(&mut self, event: Event) -> Vec<Message>;
+}
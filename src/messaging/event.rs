@@ -18,6 +18,7 @@ use crate::ValueId;
 use crate::CommandId;
 
 /// Class that represents an event from the UI to the app
+#[derive(Debug, Clone, PartialEq)]
 pub enum Event {
     RequestCommand(CommandId),
     Clicked(CommandId),
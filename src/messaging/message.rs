@@ -18,6 +18,7 @@ use crate::ScreenId;
 use crate::CommandId;
 use crate::ValueId;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ValueState {
     Enabled,
     Correct,
@@ -26,12 +27,23 @@ pub enum ValueState {
     Hidden
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum CommandState {
     Enabled,
     Disabled,
     Hidden
 }
 
+impl From<CommandState> for ValueState {
+    fn from(state: CommandState) -> Self {
+        match state {
+            CommandState::Enabled => ValueState::Enabled,
+            CommandState::Disabled => ValueState::Disabled,
+            CommandState::Hidden => ValueState::Hidden,
+        }
+    }
+}
+
 /// Class that represents a message from the App to the UI
 pub enum Message {
     ChangeScreen(ScreenId),
@@ -39,7 +51,7 @@ pub enum Message {
     PopScreen(),
     ClearScreenStack(),
 
-    CommandAvailable(CommandId, ValueState),
+    CommandAvailable(CommandId, CommandState),
     ValueChangedState(ValueId, bool, ValueState), 
     ValueChangedNumber(ValueId, i32, ValueState),
     ValueChangedFloat(ValueId, f32, ValueState),
@@ -14,11 +14,13 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
+mod app;
 mod event;
 mod message;
 
 pub type CommandId = u32;
 pub type ValueId = u32;
 
+pub use app::App;
 pub use event::Event;
-pub use message::Message;
\ No newline at end of file
+pub use message::{Message, ValueState, CommandState};
\ No newline at end of file
@@ -0,0 +1,25 @@
+/*
+Copyright 2021 Gonzalo Fernández Hernández
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+mod soft_surface;
+mod soft_window;
+
+pub use soft_surface::SoftSurface;
+
+pub fn create_headless_window(width: u32, height: u32) -> soft_window::SoftWindow {
+    soft_window::SoftWindow::new(width, height)
+}
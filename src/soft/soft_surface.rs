@@ -0,0 +1,515 @@
+/*
+Copyright 2021 Gonzalo Fernández Hernández
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+extern crate image;
+
+use crate::Color;
+use crate::Surface;
+use crate::{ImageId, ImageError, ImageProvider};
+use crate::{Transform, TransformStack};
+use crate::{default_font_bytes, FontId, FontWeight, TextStyle, TextMetrics, FontError, FontProvider};
+
+use image::RgbaImage;
+use rusttype::{Font, Scale, point};
+
+/// Software implementation for Surface.
+/// Everything is rasterized into an RGBA buffer in memory, so no GPU is needed.
+pub struct SoftSurface {
+    buffer: RgbaImage,
+    images: Vec<RgbaImage>,
+    fonts: Vec<Font<'static>>,
+    /// Opacities pushed, each one already multiplied by the ones below
+    opacity: Vec<f32>,
+    /// Rectangles pushed in pixels, each one already intersected with the ones below.
+    /// The pixels whose center is outside the last one are masked.
+    clip: Vec<(f32, f32, f32, f32)>,
+    transform: TransformStack,
+}
+
+impl SoftSurface {
+    /// Creates a surface with a transparent buffer of the given dimensions
+    pub fn new(width: u32, height: u32) -> Self {
+        let fonts = default_font_bytes().and_then(|bytes| Font::from_bytes(bytes).ok()).into_iter().collect();
+        SoftSurface{ buffer: RgbaImage::new(width.max(1_u32), height.max(1_u32)), images: Vec::new(), fonts, opacity: Vec::new(), clip: Vec::new(),
+            transform: TransformStack::new() }
+    }
+
+    pub fn dimensions(&self) -> (u32, u32) {
+        self.buffer.dimensions()
+    }
+
+    /// Changes the dimensions of the buffer. Its content is lost.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.buffer = RgbaImage::new(width.max(1_u32), height.max(1_u32));
+    }
+
+    /// The rasterized content
+    pub fn buffer(&self) -> &RgbaImage {
+        &self.buffer
+    }
+
+    /// Writes the content of the buffer into a PNG file
+    pub fn save_png(&self, path: &str) -> image::ImageResult<()> {
+        self.buffer.save_with_format(path, image::ImageFormat::Png)
+    }
+
+    /// Maps a point from (-1.0, 1.0, 1.0, -1.0) coordinates to pixels, without the transform
+    fn to_pixel(&self, x: f32, y: f32) -> (f32, f32) {
+        let (width, height) = self.buffer.dimensions();
+        ((x + 1_f32) * 0.5_f32 * width as f32, (1_f32 - y) * 0.5_f32 * height as f32)
+    }
+
+    /// Maps a point from pixels to (-1.0, 1.0, 1.0, -1.0) coordinates
+    fn to_coords(&self, x: f32, y: f32) -> (f32, f32) {
+        let (width, height) = self.buffer.dimensions();
+        (x / width as f32 * 2_f32 - 1_f32, 1_f32 - y / height as f32 * 2_f32)
+    }
+
+    /// Maps a point from (-1.0, 1.0, 1.0, -1.0) coordinates to pixels, after the transform
+    fn to_transformed_pixel(&self, x: f32, y: f32) -> (f32, f32) {
+        let (x, y) = self.transform.current().apply(x, y);
+        self.to_pixel(x, y)
+    }
+
+    /// Applies the transform to points given in pixels
+    fn transform_pixels(&self, points: &mut [(f32, f32)]) {
+        if *self.transform.current() == Transform::identity() {
+            return;
+        }
+        for point in points.iter_mut() {
+            let (x, y) = self.to_coords(point.0, point.1);
+            *point = self.to_transformed_pixel(x, y);
+        }
+    }
+
+    /// Maps a rectangle to pixels, ordered as (left, top, right, bottom), without the transform
+    fn to_pixel_rect(&self, x1: f32, y1: f32, x2: f32, y2: f32) -> (f32, f32, f32, f32) {
+        let (px1, py1) = self.to_pixel(x1, y1);
+        let (px2, py2) = self.to_pixel(x2, y2);
+        (px1.min(px2), py1.min(py2), px1.max(px2), py1.max(py2))
+    }
+
+    /// Pixels covered by a rectangle after the transform, each one with the point of the rectangle it shows
+    /// params:
+    ///     * rect: The rectangle in pixels, before the transform, as (left, top, right, bottom)
+    /// returns:
+    ///     The pixels, as (x, y, point x, point y)
+    fn map_rect(&self, rect: (f32, f32, f32, f32)) -> Vec<(i32, i32, f32, f32)> {
+        let (left, top, right, bottom) = rect;
+        let mut corners = [(left, top), (right, top), (right, bottom), (left, bottom)];
+        self.transform_pixels(&mut corners);
+        let inverse = match self.transform.current().inverse() {
+            Some(inverse) => inverse,
+            None => return Vec::new(),
+        };
+        let (width, height) = self.buffer.dimensions();
+        let min_x = corners.iter().map(|p| p.0).fold(f32::MAX, f32::min).floor().max(0_f32) as i32;
+        let max_x = corners.iter().map(|p| p.0).fold(f32::MIN, f32::max).ceil().min(width as f32) as i32;
+        let min_y = corners.iter().map(|p| p.1).fold(f32::MAX, f32::min).floor().max(0_f32) as i32;
+        let max_y = corners.iter().map(|p| p.1).fold(f32::MIN, f32::max).ceil().min(height as f32) as i32;
+        let mut pixels = Vec::new();
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let (nx, ny) = self.to_coords(x as f32 + 0.5_f32, y as f32 + 0.5_f32);
+                let (nx, ny) = inverse.apply(nx, ny);
+                let (px, py) = self.to_pixel(nx, ny);
+                // The pixel of the rectangle that contains the point must start inside it
+                let (cx, cy) = (px.floor(), py.floor());
+                if cx >= left.floor() && cx < right && cy >= top.floor() && cy < bottom {
+                    pixels.push((x, y, px, py));
+                }
+            }
+        }
+        pixels
+    }
+
+    /// Blends a color over a pixel of the buffer
+    ///     * coverage: How much of the pixel is covered [0.0 - 1.0]
+    fn blend_pixel(&mut self, x: i32, y: i32, color: &Color, coverage: f32) {
+        let (width, height) = self.buffer.dimensions();
+        if x < 0 || y < 0 || x >= width as i32 || y >= height as i32 {
+            return;
+        }
+        if let Some((left, top, right, bottom)) = self.clip.last() {
+            let (cx, cy) = (x as f32 + 0.5_f32, y as f32 + 0.5_f32);
+            if cx < *left || cx >= *right || cy < *top || cy >= *bottom {
+                return;
+            }
+        }
+        let opacity = self.opacity.last().copied().unwrap_or(1_f32);
+        let alpha = (color.a * coverage * opacity).clamp(0_f32, 1_f32);
+        if alpha <= 0_f32 {
+            return;
+        }
+        let pixel = self.buffer.get_pixel_mut(x as u32, y as u32);
+        let dst_alpha = pixel[3] as f32 / 255_f32;
+        let out_alpha = alpha + dst_alpha * (1_f32 - alpha);
+        for (i, src) in [color.r, color.g, color.b].iter().enumerate() {
+            let dst = pixel[i] as f32 / 255_f32;
+            let out = (src * alpha + dst * dst_alpha * (1_f32 - alpha)) / out_alpha;
+            pixel[i] = (out.clamp(0_f32, 1_f32) * 255_f32).round() as u8;
+        }
+        pixel[3] = (out_alpha * 255_f32).round() as u8;
+    }
+
+    /// Fills a polygon given in pixels, sampling the center of each pixel (even-odd rule)
+    fn fill_polygon(&mut self, points: &[(f32, f32)], color: &Color) {
+        if points.len() < 3 {
+            return;
+        }
+        let (width, height) = self.buffer.dimensions();
+        let min_y = points.iter().map(|p| p.1).fold(f32::MAX, f32::min).floor().max(0_f32) as i32;
+        let max_y = points.iter().map(|p| p.1).fold(f32::MIN, f32::max).ceil().min(height as f32) as i32;
+        let mut nodes = Vec::new();
+        for y in min_y..max_y {
+            let sample_y = y as f32 + 0.5_f32;
+            nodes.clear();
+            let mut j = points.len() - 1;
+            for i in 0..points.len() {
+                let ((xi, yi), (xj, yj)) = (points[i], points[j]);
+                if (yi <= sample_y && yj > sample_y) || (yj <= sample_y && yi > sample_y) {
+                    nodes.push(xi + (sample_y - yi) / (yj - yi) * (xj - xi));
+                }
+                j = i;
+            }
+            nodes.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            for pair in nodes.chunks_exact(2) {
+                let start = ((pair[0] - 0.5_f32).ceil() as i32).max(0);
+                let end = ((pair[1] - 0.5_f32).ceil() as i32).min(width as i32);
+                for x in start..end {
+                    self.blend_pixel(x, y, color, 1_f32);
+                }
+            }
+        }
+    }
+
+    /// Draws a one pixel wide line between two points given in pixels
+    fn stroke_line(&mut self, from: (f32, f32), to: (f32, f32), color: &Color) {
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let steps = dx.abs().max(dy.abs()).ceil().max(1_f32) as i32;
+        for i in 0..=steps {
+            let t = i as f32 / steps as f32;
+            self.blend_pixel((from.0 + dx * t).floor() as i32, (from.1 + dy * t).floor() as i32, color, 1_f32);
+        }
+    }
+
+    /// Draws the outline of a closed polygon given in pixels
+    fn stroke_polygon(&mut self, points: &[(f32, f32)], color: &Color) {
+        for i in 0..points.len() {
+            self.stroke_line(points[i], points[(i + 1) % points.len()], color);
+        }
+    }
+
+    /// Fills and/or outlines a polygon given in pixels
+    fn draw_polygon(&mut self, points: &[(f32, f32)], border_color: Option<&Color>, fill_color: Option<&Color>) {
+        if let Some(fc) = fill_color {
+            self.fill_polygon(points, fc);
+        }
+        if let Some(bc) = border_color {
+            self.stroke_polygon(points, bc);
+        }
+    }
+
+    /// Points of a quarter of circle, in pixels, from angle start (radians) counterclockwise
+    fn arc_points(points: &mut Vec<(f32, f32)>, cx: f32, cy: f32, radius: f32, start: f32) {
+        let num_segments = (radius as u32).max(2_u32);
+        for i in 0..=num_segments {
+            let angle = start + 0.5_f32 * std::f32::consts::PI * i as f32 / num_segments as f32;
+            points.push((cx + radius * angle.cos(), cy - radius * angle.sin()));
+        }
+    }
+}
+
+impl Surface for SoftSurface {
+
+    fn clear(&mut self, color: &Color) {
+        let pixel = image::Rgba([
+            (color.r.clamp(0_f32, 1_f32) * 255_f32).round() as u8,
+            (color.g.clamp(0_f32, 1_f32) * 255_f32).round() as u8,
+            (color.b.clamp(0_f32, 1_f32) * 255_f32).round() as u8,
+            (color.a.clamp(0_f32, 1_f32) * 255_f32).round() as u8]);
+        self.buffer.pixels_mut().for_each(|x| *x = pixel);
+    }
+
+    fn draw_line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, line_color: &Color) {
+        let from = self.to_transformed_pixel(x1, y1);
+        let to = self.to_transformed_pixel(x2, y2);
+        self.stroke_line(from, to, line_color);
+    }
+
+    fn draw_rectangle(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, border_color: Option<&Color>, fill_color: Option<&Color>) {
+        let (left, top, right, bottom) = self.to_pixel_rect(x1, y1, x2, y2);
+        let mut points = [(left, top), (right, top), (right, bottom), (left, bottom)];
+        self.transform_pixels(&mut points);
+        self.draw_polygon(&points, border_color, fill_color);
+    }
+
+    fn draw_triangle(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x3: f32, y3: f32, border_color: Option<&Color>, fill_color: Option<&Color>) {
+        let points = [self.to_transformed_pixel(x1, y1), self.to_transformed_pixel(x2, y2), self.to_transformed_pixel(x3, y3)];
+        self.draw_polygon(&points, border_color, fill_color);
+    }
+
+    fn draw_ellipse(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, border_color: Option<&Color>, fill_color: Option<&Color>) {
+        let (left, top, right, bottom) = self.to_pixel_rect(x1, y1, x2, y2);
+        let (rx, ry) = ((right - left) * 0.5_f32, (bottom - top) * 0.5_f32);
+        let (cx, cy) = (left + rx, top + ry);
+        let num_segments = (rx.max(ry) as u32).max(8_u32);
+        let mut points: Vec<(f32, f32)> = (0..num_segments).map(|i| {
+            let angle = 2_f32 * std::f32::consts::PI * i as f32 / num_segments as f32;
+            (cx + rx * angle.cos(), cy + ry * angle.sin())
+        }).collect();
+        self.transform_pixels(&mut points);
+        self.draw_polygon(&points, border_color, fill_color);
+    }
+
+    fn draw_circle(&mut self, x: f32, y: f32, radius: f32, border_color: Option<&Color>, fill_color: Option<&Color>) {
+        let (width, height) = self.buffer.dimensions();
+        let vertical = radius * width as f32 / height as f32;
+        self.draw_ellipse(x - radius, y + vertical, x + radius, y - vertical, border_color, fill_color);
+    }
+
+    fn draw_rounded_rectangle(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, rad_pixel: u32, border_color: Option<&Color>, fill_color: Option<&Color>) {
+        let (left, top, right, bottom) = self.to_pixel_rect(x1, y1, x2, y2);
+        let radius = (rad_pixel as f32).min((right - left) * 0.5_f32).min((bottom - top) * 0.5_f32);
+        let half_pi = 0.5_f32 * std::f32::consts::PI;
+        let mut points = Vec::new();
+        SoftSurface::arc_points(&mut points, right - radius, top + radius, radius, 0_f32);
+        SoftSurface::arc_points(&mut points, left + radius, top + radius, radius, half_pi);
+        SoftSurface::arc_points(&mut points, left + radius, bottom - radius, radius, 2_f32 * half_pi);
+        SoftSurface::arc_points(&mut points, right - radius, bottom - radius, radius, 3_f32 * half_pi);
+        self.transform_pixels(&mut points);
+        self.draw_polygon(&points, border_color, fill_color);
+    }
+
+    fn draw_text(&mut self, text: &str, x1: f32, y1: f32, x2: f32, y2: f32, text_color: &Color, style: &TextStyle) {
+        let (left, top, right, bottom) = self.to_pixel_rect(x1, y1, x2, y2);
+        let offsets: &[i32] = match style.weight {
+            FontWeight::Regular => &[0],
+            FontWeight::Bold => &[0, 1],
+        };
+        // The glyphs are rasterized without the transform, and then mapped like an image
+        let (origin_x, origin_y) = (left.floor(), top.floor());
+        let columns = (right.ceil() - origin_x).max(0_f32) as usize;
+        let rows = (bottom.ceil() - origin_y).max(0_f32) as usize;
+        let mut coverage = vec![0_f32; columns * rows];
+        if let Some(font) = self.fonts.get(style.font) {
+            let scale = Scale::uniform(style.size);
+            let ascent = font.v_metrics(scale).ascent;
+            for glyph in font.layout(text, scale, point(left, top + ascent)) {
+                if let Some(bounds) = glyph.pixel_bounding_box() {
+                    glyph.draw(|gx, gy, value| {
+                        for offset in offsets {
+                            let x = bounds.min.x + gx as i32 + offset - origin_x as i32;
+                            let y = bounds.min.y + gy as i32 - origin_y as i32;
+                            if x >= 0 && (x as usize) < columns && y >= 0 && (y as usize) < rows {
+                                let cell = &mut coverage[y as usize * columns + x as usize];
+                                *cell = cell.max(value);
+                            }
+                        }
+                    });
+                }
+            }
+        }
+        for (x, y, px, py) in self.map_rect((left, top, right, bottom)) {
+            let (column, row) = ((px - origin_x) as usize, (py - origin_y) as usize);
+            if column < columns && row < rows && coverage[row * columns + column] > 0_f32 {
+                self.blend_pixel(x, y, text_color, coverage[row * columns + column]);
+            }
+        }
+    }
+
+    fn push_opacity(&mut self, opacity: f32) {
+        let current = self.opacity.last().copied().unwrap_or(1_f32);
+        self.opacity.push(current * opacity.clamp(0_f32, 1_f32));
+    }
+
+    fn pop_opacity(&mut self) {
+        self.opacity.pop();
+    }
+
+    fn push_clip(&mut self, x1: f32, y1: f32, x2: f32, y2: f32) {
+        let (x1, y1, x2, y2) = self.transform.current().apply_bounds(x1, y1, x2, y2);
+        let (mut left, mut top, mut right, mut bottom) = self.to_pixel_rect(x1, y1, x2, y2);
+        if let Some(last) = self.clip.last() {
+            left = left.max(last.0);
+            top = top.max(last.1);
+            right = right.min(last.2).max(left);
+            bottom = bottom.min(last.3).max(top);
+        }
+        self.clip.push((left, top, right, bottom));
+    }
+
+    fn pop_clip(&mut self) {
+        self.clip.pop();
+    }
+
+    fn push_transform(&mut self) {
+        self.transform.push();
+    }
+
+    fn pop_transform(&mut self) {
+        self.transform.pop();
+    }
+
+    fn translate(&mut self, x: f32, y: f32) {
+        self.transform.translate(x, y);
+    }
+
+    fn scale(&mut self, x: f32, y: f32) {
+        self.transform.scale(x, y);
+    }
+
+    fn rotate(&mut self, angle: f32) {
+        let (width, height) = self.buffer.dimensions();
+        self.transform.rotate(angle, width as f32 / height as f32);
+    }
+
+    fn draw_image_region(&mut self, img: ImageId, x1: f32, y1: f32, x2: f32, y2: f32, region: (f32, f32, f32, f32)) {
+        if img >= self.images.len() {
+            return;
+        }
+        let (left, top, right, bottom) = self.to_pixel_rect(x1, y1, x2, y2);
+        let (img_width, img_height) = self.images[img].dimensions();
+        let (width, height) = (right - left, bottom - top);
+        if width <= 0_f32 || height <= 0_f32 || img_width == 0 || img_height == 0 {
+            return;
+        }
+        let (u1, v1, u2, v2) = region;
+        for (x, y, px, py) in self.map_rect((left, top, right, bottom)) {
+            let u = u1 + (px - left) / width * (u2 - u1);
+            let v = v1 + (py - top) / height * (v2 - v1);
+            let u = ((u * img_width as f32) as u32).min(img_width - 1);
+            let v = ((v * img_height as f32) as u32).min(img_height - 1);
+            let pixel = self.images[img].get_pixel(u, v);
+            let color = Color{
+                r: pixel[0] as f32 / 255_f32,
+                g: pixel[1] as f32 / 255_f32,
+                b: pixel[2] as f32 / 255_f32,
+                a: pixel[3] as f32 / 255_f32};
+            self.blend_pixel(x, y, &color, 1_f32);
+        }
+    }
+}
+
+impl ImageProvider for SoftSurface {
+
+    fn load_image_from_rgba(&mut self, width: u32, height: u32, pixels: Vec<u8>) -> Result<ImageId, ImageError> {
+        let image = RgbaImage::from_raw(width, height, pixels).ok_or(ImageError::InvalidSize)?;
+        self.images.push(image);
+        Ok(self.images.len() - 1)
+    }
+
+    fn image_size(&self, img: ImageId) -> Option<(u32, u32)> {
+        self.images.get(img).map(|x| x.dimensions())
+    }
+}
+
+impl FontProvider for SoftSurface {
+
+    fn load_font_from_bytes(&mut self, bytes: Vec<u8>) -> Result<FontId, FontError> {
+        let font = Font::from_bytes(bytes).map_err(|_| FontError::InvalidFont)?;
+        self.fonts.push(font);
+        Ok(self.fonts.len() - 1)
+    }
+
+    fn measure_text(&self, text: &str, style: &TextStyle) -> TextMetrics {
+        let font = match self.fonts.get(style.font) {
+            Some(font) => font,
+            None => return TextMetrics::default(),
+        };
+        let scale = Scale::uniform(style.size);
+        let v_metrics = font.v_metrics(scale);
+        let mut width = font.layout(text, scale, point(0_f32, 0_f32)).last()
+            .map(|x| x.position().x + x.unpositioned().h_metrics().advance_width)
+            .unwrap_or(0_f32);
+        if style.weight == FontWeight::Bold && width > 0_f32 {
+            width += 1_f32;
+        }
+        TextMetrics{width, height: v_metrics.ascent - v_metrics.descent, baseline: v_metrics.ascent}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK: Color = Color{r: 0_f32, g: 0_f32, b: 0_f32, a: 1_f32};
+    const RED: Color = Color{r: 1_f32, g: 0_f32, b: 0_f32, a: 1_f32};
+    const WHITE: Color = Color{r: 1_f32, g: 1_f32, b: 1_f32, a: 1_f32};
+
+    fn surface() -> SoftSurface {
+        let mut surface = SoftSurface::new(100, 100);
+        surface.clear(&BLACK);
+        surface
+    }
+
+    fn pixel(surface: &SoftSurface, x: u32, y: u32) -> [u8; 4] {
+        surface.buffer().get_pixel(x, y).0
+    }
+
+    #[test]
+    fn rectangles_fill_their_pixels() {
+        let mut surface = surface();
+        surface.draw_rectangle(-0.5_f32, 0.5_f32, 0.5_f32, -0.5_f32, None, Some(&RED));
+        assert_eq!(pixel(&surface, 50, 50), [255, 0, 0, 255]);
+        assert_eq!(pixel(&surface, 26, 74), [255, 0, 0, 255]);
+        assert_eq!(pixel(&surface, 10, 50), [0, 0, 0, 255]);
+        assert_eq!(pixel(&surface, 50, 90), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn clipping_masks_the_pixels_outside() {
+        let mut surface = surface();
+        surface.push_clip(-1_f32, 1_f32, 0_f32, -1_f32);
+        surface.push_clip(-1_f32, 0_f32, 1_f32, -1_f32);
+        surface.draw_rectangle(-1_f32, 1_f32, 1_f32, -1_f32, None, Some(&RED));
+        surface.pop_clip();
+        assert_eq!(pixel(&surface, 25, 75), [255, 0, 0, 255]);
+        assert_eq!(pixel(&surface, 25, 25), [0, 0, 0, 255]);
+        assert_eq!(pixel(&surface, 75, 75), [0, 0, 0, 255]);
+
+        surface.pop_clip();
+        surface.draw_rectangle(0_f32, 1_f32, 1_f32, 0_f32, None, Some(&RED));
+        assert_eq!(pixel(&surface, 75, 25), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn opacity_blends_with_the_pixels_below() {
+        let mut surface = surface();
+        surface.push_opacity(0.5_f32);
+        surface.draw_rectangle(-1_f32, 1_f32, 0_f32, -1_f32, None, Some(&WHITE));
+        surface.push_opacity(0.5_f32);
+        surface.draw_rectangle(0_f32, 1_f32, 1_f32, -1_f32, None, Some(&WHITE));
+        surface.pop_opacity();
+        surface.pop_opacity();
+        assert_eq!(pixel(&surface, 25, 50), [128, 128, 128, 255]);
+        assert_eq!(pixel(&surface, 75, 50), [64, 64, 64, 255]);
+
+        surface.draw_rectangle(-1_f32, 1_f32, 1_f32, 0_f32, None, Some(&WHITE));
+        assert_eq!(pixel(&surface, 75, 25), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn png_keeps_the_pixels() {
+        let mut surface = surface();
+        surface.draw_rectangle(-0.5_f32, 0.5_f32, 0.5_f32, -0.5_f32, Some(&WHITE), Some(&RED));
+        let path = std::env::temp_dir().join(format!("clearview_soft_surface_{}.png", std::process::id()));
+        surface.save_png(path.to_str().unwrap()).unwrap();
+        let read = image::open(&path).map(|x| x.to_rgba8());
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read.unwrap(), *surface.buffer());
+    }
+}
@@ -0,0 +1,318 @@
+/*
+Copyright 2021 Gonzalo Fernández Hernández
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+use std::collections::VecDeque;
+
+use crate::{Chord, CommandId};
+use crate::{Screen, ScreenStack, Window, Surface, Color, ScreenId, UpdateData, MouseStatus, KeyboardStatus, Key, Modifiers, App, Event};
+use crate::{FontId, TextStyle, TextMetrics, FontError, FontProvider};
+use crate::{ImageId, ImageError, ImageProvider};
+use crate::soft::SoftSurface;
+
+/// A step of the input given to a SoftWindow
+///     * MouseMove: Moves the mouse to a position in pixels
+///     * MouseDown: Presses the main mouse button
+///     * MouseUp: Releases the main mouse button
+///     * Click: Moves the mouse to a position, presses and releases the button
+///     * Wheel: Scrolls some lines with the mouse wheel; positive is up
+///     * KeyDown: Presses a key
+///     * KeyRepeat: Repeats a key held down
+///     * KeyUp: Releases a key
+///     * Modifiers: Changes the modifier keys held down
+///     * Type: Types some characters
+///     * Resize: Changes the dimensions of the window
+///     * Frame: Updates and paints the screen without any input
+#[derive(Debug, Clone)]
+pub enum InputStep {
+    MouseMove(u32, u32),
+    MouseDown,
+    MouseUp,
+    Click(u32, u32),
+    Wheel(f32),
+    KeyDown(Key),
+    KeyRepeat(Key),
+    KeyUp(Key),
+    Modifiers(Modifiers),
+    Type(String),
+    Resize(u32, u32),
+    Frame,
+}
+
+/// Window without an OS window. It is driven by a script of input steps
+/// and paints into a SoftSurface, so it can be used in tests.
+pub struct SoftWindow {
+    screens: ScreenStack,
+    surface: SoftSurface,
+    script: VecDeque<InputStep>,
+    update_data: UpdateData,
+}
+
+impl SoftWindow {
+    /// Creates a headless window
+    /// params:
+    ///     * width: Width of the window
+    ///     * height: Height of the window
+    pub fn new(width: u32, height: u32) -> Self {
+        SoftWindow {
+            screens: ScreenStack::new(width, height),
+            surface: SoftSurface::new(width, height),
+            script: VecDeque::new(),
+            update_data: UpdateData::new(),
+        }
+    }
+
+    /// Adds a step at the end of the input script
+    pub fn push_input(&mut self, step: InputStep) {
+        self.script.push_back(step);
+    }
+
+    /// Adds several steps at the end of the input script
+    pub fn push_inputs(&mut self, steps: &[InputStep]) {
+        self.script.extend(steps.iter().cloned());
+    }
+
+    /// Surface where the screens have been painted
+    pub fn surface(&self) -> &SoftSurface {
+        &self.surface
+    }
+
+    /// Runs the pending input script. Every step updates and paints the screen on top of the stack.
+    /// params:
+    ///     * app: The application that handles the events
+    /// returns:
+    ///     The events produced, in order
+    pub fn run(&mut self, app: &mut dyn App) -> Vec<Event> {
+        let mut events = Vec::new();
+        while let Some(step) = self.script.pop_front() {
+            match step {
+                InputStep::MouseMove(x, y) => self.update_data.mouse_position = (x, y),
+                InputStep::MouseDown => self.update_data.mouse_status = MouseStatus::Pressed,
+                InputStep::MouseUp => self.update_data.mouse_status = MouseStatus::Released,
+                InputStep::Click(x, y) => {
+                    self.script.push_front(InputStep::MouseUp);
+                    self.script.push_front(InputStep::MouseDown);
+                    self.update_data.mouse_position = (x, y);
+                },
+                InputStep::Wheel(lines) => self.update_data.wheel += lines,
+                InputStep::KeyDown(key) => self.update_data.keyboard_status = KeyboardStatus::KeyDown(key),
+                InputStep::KeyRepeat(key) => {
+                    self.update_data.keyboard_status = KeyboardStatus::KeyDown(key);
+                    self.update_data.key_repeat = true;
+                },
+                InputStep::KeyUp(key) => self.update_data.keyboard_status = KeyboardStatus::KeyUp(key),
+                InputStep::Modifiers(modifiers) => self.update_data.modifiers = modifiers,
+                InputStep::Type(text) => self.update_data.text = text,
+                InputStep::Resize(width, height) => {
+                    self.screens.resize(width, height);
+                    self.surface.resize(width, height);
+                },
+                InputStep::Frame => (),
+            }
+            events.append(&mut self.step(app));
+        }
+        events
+    }
+
+    /// Updates and paints the screens once
+    fn step(&mut self, app: &mut dyn App) -> Vec<Event> {
+        let events = self.screens.update(&self.update_data);
+        for event in events.iter() {
+            for message in app.handle_event(event.clone()) {
+                self.screens.process_message(&message);
+            }
+        }
+        self.update_data.reset();
+
+        self.surface.clear(&Color{r: 0_f32, g: 0_f32, b: 0_f32, a: 1_f32});
+        self.screens.paint(&mut self.surface);
+        events
+    }
+}
+
+impl Window for SoftWindow {
+
+    fn add_screen(&mut self, screen: Screen) -> ScreenId {
+        self.screens.add_screen(screen)
+    }
+
+    fn add_accelerator(&mut self, chords: &[Chord], command: CommandId) {
+        self.screens.add_accelerator(chords, command);
+    }
+
+    /// Runs the input script, sending the events to the app
+    fn execute(mut self, mut app: Box<dyn App>) {
+        self.run(app.as_mut());
+    }
+}
+
+impl FontProvider for SoftWindow {
+
+    fn load_font_from_bytes(&mut self, bytes: Vec<u8>) -> Result<FontId, FontError> {
+        self.surface.load_font_from_bytes(bytes)
+    }
+
+    fn measure_text(&self, text: &str, style: &TextStyle) -> TextMetrics {
+        self.surface.measure_text(text, style)
+    }
+}
+
+impl ImageProvider for SoftWindow {
+
+    fn load_image_from_rgba(&mut self, width: u32, height: u32, pixels: Vec<u8>) -> Result<ImageId, ImageError> {
+        self.surface.load_image_from_rgba(width, height, pixels)
+    }
+
+    fn image_size(&self, img: ImageId) -> Option<(u32, u32)> {
+        self.surface.image_size(img)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Layout, Widget, Theme, Direction, Sense, Message};
+    use crate::soft::create_headless_window;
+
+    /// Handles no event
+    struct NullApp;
+
+    impl App for NullApp {
+        fn handle_event(&mut self, _event: Event) -> Vec<Message> {
+            Vec::new()
+        }
+    }
+
+    /// Opens the screen 1 when the button 1 is clicked
+    struct OpenApp;
+
+    impl App for OpenApp {
+        fn handle_event(&mut self, event: Event) -> Vec<Message> {
+            match event {
+                Event::Clicked(1) => vec![Message::PushScreen(1)],
+                _ => Vec::new(),
+            }
+        }
+    }
+
+    /// Two buttons, 1 on the left half and 2 on the right half
+    fn two_buttons(layout: &mut Layout) {
+        let theme = Theme::new();
+        let (left, right) = layout.split(0.5_f32, Direction::Horizontal, Sense::Forward).unwrap();
+        left.set_widget(Widget::new_button(1, theme));
+        right.set_widget(Widget::new_button(2, theme));
+    }
+
+    /// A spin box for the value 3 between the buttons 1 and 2
+    fn spin_between_buttons(layout: &mut Layout) {
+        let theme = Theme::new();
+        let (left, rest) = layout.trim(100, Direction::Horizontal, Sense::Forward).unwrap();
+        let (middle, right) = rest.trim(100, Direction::Horizontal, Sense::Forward).unwrap();
+        left.set_widget(Widget::new_button(1, theme));
+        middle.set_widget(Widget::new_spin_box(3, 7, 0, 10, 1, theme));
+        right.set_widget(Widget::new_button(2, theme));
+    }
+
+    /// The button 3 on the left half, and nothing on the right half
+    fn left_button(layout: &mut Layout) {
+        let (left, _) = layout.split(0.5_f32, Direction::Horizontal, Sense::Forward).unwrap();
+        left.set_widget(Widget::new_button(3, Theme::new()));
+    }
+
+    fn window() -> SoftWindow {
+        let mut window = create_headless_window(200, 100);
+        window.add_screen(Screen::new(two_buttons));
+        window
+    }
+
+    fn clicks(events: &[Event]) -> Vec<CommandId> {
+        events.iter().filter_map(|x| match x {
+            Event::Clicked(command) => Some(*command),
+            _ => None,
+        }).collect()
+    }
+
+    #[test]
+    fn click_on_a_button() {
+        let mut window = window();
+        window.push_input(InputStep::Click(50, 50));
+        assert_eq!(clicks(&window.run(&mut NullApp)), vec![1]);
+        window.push_input(InputStep::Click(150, 50));
+        assert_eq!(clicks(&window.run(&mut NullApp)), vec![2]);
+    }
+
+    #[test]
+    fn release_outside_the_button_does_not_click() {
+        let mut window = window();
+        window.push_inputs(&[
+            InputStep::MouseMove(50, 50),
+            InputStep::MouseDown,
+            InputStep::MouseMove(150, 50),
+            InputStep::MouseUp,
+        ]);
+        assert_eq!(clicks(&window.run(&mut NullApp)), Vec::<CommandId>::new());
+    }
+
+    #[test]
+    fn resize_moves_the_buttons() {
+        let mut window = window();
+        window.push_inputs(&[InputStep::Resize(400, 100), InputStep::Click(150, 50), InputStep::Click(300, 50)]);
+        assert_eq!(clicks(&window.run(&mut NullApp)), vec![1, 2]);
+        assert_eq!(window.surface().dimensions(), (400, 100));
+    }
+
+    #[test]
+    fn horizontal_arrows_move_the_focus_off_a_spin_box() {
+        let mut window = create_headless_window(300, 100);
+        window.add_screen(Screen::new(spin_between_buttons));
+        // The click leaves the caret at the end of "7"
+        window.push_inputs(&[InputStep::Click(140, 50), InputStep::KeyDown(Key::Up), InputStep::KeyDown(Key::Down)]);
+        let events = window.run(&mut NullApp);
+        assert_eq!(events, vec![Event::NumberInserted(3, 8), Event::NumberInserted(3, 7)]);
+
+        window.push_inputs(&[InputStep::KeyDown(Key::Right), InputStep::KeyDown(Key::Enter)]);
+        assert_eq!(clicks(&window.run(&mut NullApp)), vec![2]);
+
+        // Left first moves the caret to the start of the text, and then the focus
+        window.push_inputs(&[InputStep::Click(140, 50), InputStep::KeyDown(Key::Left), InputStep::KeyDown(Key::Enter)]);
+        assert_eq!(clicks(&window.run(&mut NullApp)), Vec::<CommandId>::new());
+        window.push_inputs(&[
+            InputStep::Click(140, 50),
+            InputStep::KeyDown(Key::Left),
+            InputStep::KeyDown(Key::Left),
+            InputStep::KeyDown(Key::Enter),
+        ]);
+        assert_eq!(clicks(&window.run(&mut NullApp)), vec![1]);
+    }
+
+    #[test]
+    fn a_modal_screen_blocks_the_screen_below() {
+        let mut window = window();
+        window.add_screen(Screen::new_modal(left_button));
+        window.push_input(InputStep::Click(50, 50));
+        assert_eq!(clicks(&window.run(&mut OpenApp)), vec![1]);
+        // The button 2 is painted under the modal screen, but does not get the mouse or the keyboard
+        window.push_inputs(&[
+            InputStep::Click(150, 50),
+            InputStep::KeyDown(Key::Tab),
+            InputStep::KeyDown(Key::Tab),
+            InputStep::KeyDown(Key::Enter),
+        ]);
+        assert_eq!(clicks(&window.run(&mut OpenApp)), vec![3]);
+        window.push_input(InputStep::Click(50, 50));
+        assert_eq!(clicks(&window.run(&mut OpenApp)), vec![3]);
+    }
+}
@@ -0,0 +1,121 @@
+/*
+Copyright 2021 Gonzalo Fernández Hernández
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+use std::fmt;
+
+pub type FontId = usize;
+
+/// Locations where a default font is looked for, in order
+const DEFAULT_FONT_PATHS: [&str; 6] = [
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+    "/System/Library/Fonts/Supplemental/Arial.ttf",
+    "/Library/Fonts/Arial.ttf",
+    "C:\\Windows\\Fonts\\arial.ttf",
+];
+
+/// Reads the first default font found in the system.
+/// returns:
+///     The bytes of the font file, or None if no font has been found.
+pub fn default_font_bytes() -> Option<Vec<u8>> {
+    DEFAULT_FONT_PATHS.iter().find_map(|path| std::fs::read(path).ok())
+}
+
+/// Weight of the strokes of a text
+///     * Regular: As designed in the font
+///     * Bold: Thicker strokes (synthesized from the font)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FontWeight {
+    Regular,
+    Bold,
+}
+
+/// How a text is drawn
+///     * font: Font loaded in the surface. The default font of the system, if found, is always 0.
+///     * size: Height of the font in pixels
+///     * weight: Weight of the strokes
+#[derive(Debug, Clone, Copy)]
+pub struct TextStyle {
+    pub font: FontId,
+    pub size: f32,
+    pub weight: FontWeight,
+}
+
+impl TextStyle {
+    pub fn new(font: FontId, size: f32, weight: FontWeight) -> Self {
+        TextStyle{font, size, weight}
+    }
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        TextStyle{font: 0, size: 16_f32, weight: FontWeight::Regular}
+    }
+}
+
+/// Measures of a text, in pixels
+///     * width: Horizontal advance of the whole text
+///     * height: Height of a line (ascent + descent)
+///     * baseline: Distance from the top of the line to the baseline
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TextMetrics {
+    pub width: f32,
+    pub height: f32,
+    pub baseline: f32,
+}
+
+/// Error loading a font
+///     * Io: The file could not be read
+///     * InvalidFont: The data is not a font that can be used
+#[derive(Debug)]
+pub enum FontError {
+    Io(std::io::Error),
+    InvalidFont,
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FontError::Io(error) => write!(f, "Cannot read font: {}", error),
+            FontError::InvalidFont => write!(f, "Invalid font data"),
+        }
+    }
+}
+
+impl std::error::Error for FontError {}
+
+impl From<std::io::Error> for FontError {
+    fn from(error: std::io::Error) -> Self {
+        FontError::Io(error)
+    }
+}
+
+/// Loads fonts and measures texts. The measures are the same the text has when drawn.
+pub trait FontProvider {
+    /// Loads a font from a TTF/OTF file and returns its id
+    fn load_font_from_file(&mut self, path: &str) -> Result<FontId, FontError> {
+        let bytes = std::fs::read(path)?;
+        self.load_font_from_bytes(bytes)
+    }
+
+    /// Loads a font from the content of a TTF/OTF file and returns its id
+    fn load_font_from_bytes(&mut self, bytes: Vec<u8>) -> Result<FontId, FontError>;
+
+    /// Measures a text drawn in a style. An unknown font measures zero.
+    fn measure_text(&self, text: &str, style: &TextStyle) -> TextMetrics;
+}
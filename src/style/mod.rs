@@ -15,7 +15,9 @@ limitations under the License.
 */
 
 mod color;
+mod font;
 mod theme;
 
 pub use color::Color;
-pub use theme::Theme;
+pub use font::{default_font_bytes, FontId, FontWeight, TextStyle, TextMetrics, FontError, FontProvider};
+pub use theme::{Theme, WidgetStyle};
@@ -15,17 +15,18 @@ limitations under the License.
 */
 
 use crate::Color;
+use crate::TextFormat;
 
 /// Styles for the widgets
 ///     * Classic:  Hard edge widgets, straight lines
 ///     * Modern:   Rounded widgets
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub enum WidgetStyle {
     Classic,
     Modern,
 }
 
-/// Colors for the widgets
+/// Colors and text format for the widgets
 #[derive(Clone, Copy)]
 pub struct Theme {
     pub style: WidgetStyle,
@@ -35,7 +36,10 @@ pub struct Theme {
     pub wrong: Color,
     pub hover: Color,
     pub border: Color,
+    pub focus: Color,
     pub background: Color,
+    pub text: Color,
+    pub text_format: TextFormat,
 }
 
 impl Theme {
@@ -48,7 +52,10 @@ impl Theme {
             wrong: Color {r: 1_f32, g: 0_f32, b: 0_f32, a: 1_f32},
             hover: Color {r: 0_f32, g: 1_f32, b: 1_f32, a: 1_f32},
             border: Color {r: 0_f32, g: 0_f32, b: 0_f32, a: 1_f32},
+            focus: Color {r: 0_f32, g: 0.4706_f32, b: 0.8431_f32, a: 1_f32},
             background: Color {r: 0.9412_f32, g: 0.9412_f32, b: 0.9412_f32, a: 1_f32},
+            text: Color {r: 0_f32, g: 0_f32, b: 0_f32, a: 1_f32},
+            text_format: TextFormat::default(),
         }
     }
 }
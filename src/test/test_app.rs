@@ -17,50 +17,112 @@ limitations under the License.
 use crate::create_window;
 use crate::Window;
 use crate::{Layout, Direction, Sense};
-use crate::Widget;
+use crate::{Widget, SliderOptions, SliderEmit};
 use crate::Screen;
 use crate::Color;
 use crate::Panel;
 use crate::Theme;
+use crate::{App, Event, Message, ValueState};
+use crate::{Chord, Key};
 
-/*struct MyApp {
+struct MyApp {
+}
 
-}*/
+impl App for MyApp {
+    fn handle_event(&mut self, event: Event) -> Vec<Message> {
+        match event {
+            Event::Clicked(command) => {
+                return vec![Message::ValueChangedText(1, format!("Button {} clicked", command), ValueState::Enabled)];
+            },
+            Event::RequestCommand(command) => {
+                return vec![Message::ValueChangedText(1, format!("Command {} requested", command), ValueState::Enabled)];
+            },
+            Event::StateChanged(value, state) => {
+                return vec![Message::ValueChangedText(1, format!("Value {} is {}", value, state), ValueState::Enabled)];
+            },
+            Event::NumberInserted(value, number) => {
+                let state = if value == 5 && number > 50 { ValueState::Wrong } else { ValueState::Enabled };
+                return vec![
+                    Message::ValueChangedNumber(value, number, state),
+                    Message::ValueChangedText(1, format!("Value {} is {}", value, number), ValueState::Enabled),
+                ];
+            },
+            Event::FloatInserted(value, number) => {
+                return vec![Message::ValueChangedText(1, format!("Value {} is {}", value, number), ValueState::Enabled)];
+            },
+            Event::TextInserted(_, text) => {
+                return vec![Message::ValueChangedText(1, format!("Text: {}", text), ValueState::Enabled)];
+            },
+            Event::Quit() => println!("Bye"),
+            _ => (),
+        }
+        Vec::new()
+    }
+}
 
 pub fn main() {
     let mut window = create_window("Test", 800_u32, 600_u32, true);
 
-    window.add_screen(Screen::new(main_screen));
-    window.execute();
+    let mut screen = Screen::new(main_screen);
+    screen.add_accelerator(&[Chord::ctrl(Key::S)], 7);
+    window.add_screen(screen);
+    window.add_accelerator(&[Chord::key(Key::F5)], 8);
+    window.add_accelerator(&[Chord::ctrl(Key::K), Chord::ctrl(Key::C)], 9);
+    window.execute(Box::new(MyApp{}));
 
 }
 
 pub fn main_toolbar(layout: &mut Layout) -> &mut Layout {
     let theme = Theme::new();
-    let signal = 0u32;
 
     let (toolbar_sp, remaining_sp) = layout.trim(158_u32, Direction::Vertical, Sense::Forward).unwrap();
     let toolbar_content = toolbar_sp.set_panel(15_u32, Panel::new(theme.background, None));
 
     let (button, remaining_buttons) = toolbar_content.trim(128u32, Direction::Horizontal, Sense::Forward).unwrap();
-    button.set_widget(Widget::new_button(signal, theme));
+    button.set_widget(Widget::new_button(1u32, theme));
     let (button, remaining_buttons) = remaining_buttons.trim(128u32, Direction::Horizontal, Sense::Forward).unwrap();
-    button.set_widget(Widget::new_button(signal, theme));
+    button.set_widget(Widget::new_button(2u32, theme));
     let (button, remaining_buttons) = remaining_buttons.trim(128u32, Direction::Horizontal, Sense::Forward).unwrap();
-    button.set_widget(Widget::new_button(signal, theme));
+    button.set_widget(Widget::new_button(3u32, theme));
     let (button, remaining_buttons) = remaining_buttons.trim(128u32, Direction::Horizontal, Sense::Forward).unwrap();
-    button.set_widget(Widget::new_button(signal, theme));
+    button.set_widget(Widget::new_button(4u32, theme));
     let (button, _remaining_buttons) = remaining_buttons.trim(128u32, Direction::Horizontal, Sense::Forward).unwrap();
-    button.set_widget(Widget::new_button(signal, theme));
+    button.set_widget(Widget::new_button(5u32, theme));
     remaining_sp
 }
 
 pub fn main_screen(layout: &mut Layout) {
     let theme = Theme::new();
-    let signal = 0u32;
     let client_sp = main_toolbar(layout);
     let (button_sp, second_sp) = client_sp.trim(100_u32, Direction::Horizontal, Sense::Forward).unwrap();
-    button_sp.set_widget(Widget::new_button(signal, theme));
-    let (_first_sp, second_but_sp) = second_sp.trim(200_u32, Direction::Vertical, Sense::Forward).unwrap();
-    second_but_sp.set_widget(Widget::new_button(signal, theme));
+    button_sp.set_widget(Widget::new_button(6u32, theme));
+    let (input_sp, second_sp) = second_sp.trim(60_u32, Direction::Vertical, Sense::Forward).unwrap();
+    let input_sp = input_sp.set_panel(10_u32, Panel::new(theme.background, None));
+    input_sp.set_widget(Widget::new_text_input(2, "Type here", theme));
+    let (checks_sp, second_sp) = second_sp.trim(40_u32, Direction::Vertical, Sense::Forward).unwrap();
+    let checks_sp = checks_sp.set_panel(5_u32, Panel::new(theme.background, None));
+    let (check_sp, toggle_sp) = checks_sp.split(0.5_f32, Direction::Horizontal, Sense::Forward).unwrap();
+    check_sp.set_widget(Widget::new_checkbox(3, "Check me", false, theme));
+    toggle_sp.set_widget(Widget::new_toggle(4, "Switch me", true, theme));
+    let (spins_sp, second_sp) = second_sp.trim(40_u32, Direction::Vertical, Sense::Forward).unwrap();
+    let spins_sp = spins_sp.set_panel(5_u32, Panel::new(theme.background, None));
+    let (int_sp, float_sp) = spins_sp.split(0.5_f32, Direction::Horizontal, Sense::Forward).unwrap();
+    int_sp.set_widget(Widget::new_spin_box(5, 10, 0, 100, 1, theme));
+    float_sp.set_widget(Widget::new_float_spin_box(6, 0.5_f32, 0_f32, 1_f32, 0.05_f32, 2, theme));
+    let (sliders_sp, second_sp) = second_sp.trim(30_u32, Direction::Vertical, Sense::Forward).unwrap();
+    let sliders_sp = sliders_sp.set_panel(0_u32, Panel::new(theme.background, None));
+    let (slider_sp, range_sp) = sliders_sp.split(0.5_f32, Direction::Horizontal, Sense::Forward).unwrap();
+    let options = SliderOptions{step: 5_f32, ticks: Some(10_f32), ..SliderOptions::default()};
+    slider_sp.set_widget(Widget::new_slider(7, 50, 0, 100, options, theme));
+    let options = SliderOptions{step: 0.1_f32, emit: SliderEmit::OnRelease, ..SliderOptions::default()};
+    range_sp.set_widget(Widget::new_float_range_slider((8, 9), (0.2_f32, 0.8_f32), 0_f32, 1_f32, options, theme));
+    let (label_sp, second_but_sp) = second_sp.trim(90_u32, Direction::Vertical, Sense::Forward).unwrap();
+    let label_sp = label_sp.set_panel(10_u32, Panel::new_themed(theme));
+    let (vertical_sp, label_sp) = label_sp.trim(30_u32, Direction::Horizontal, Sense::Backwards).unwrap();
+    label_sp.set_widget(Widget::new_label("Click a button", Some(1), theme));
+    let options = SliderOptions{direction: Direction::Vertical, step: 1_f32, ticks: Some(2_f32), ..SliderOptions::default()};
+    vertical_sp.set_widget(Widget::new_slider(10, 3, 0, 10, options, theme));
+    let mut save = Widget::new_text_button(7u32, "Save", theme);
+    save.set_accelerator(&[Chord::ctrl(Key::S)]);
+    second_but_sp.set_widget(save);
 }
\ No newline at end of file
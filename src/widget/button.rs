@@ -14,9 +14,78 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
-use crate::{Surface, Space, Color};
+use crate::{Surface, UpdateData, MouseStatus, KeyboardStatus, Key, Event, Color, WidgetStyle};
+use crate::{TextLayout, TextFormat, HorizontalAlign, VerticalAlign, TextStyle};
+use crate::widget::{Widget, WidgetInfo, WidgetStatus};
 
-pub fn paint_button(space: &Space, surface: &mut dyn Surface, color_fg: &Color, color_bg: &Color, color_border: &Color) {
+/// Space in pixels between the border of the button and its texts
+const PADDING: u32 = 4;
+
+/// Radius in pixels of the corners in the Modern style
+const RADIUS: u32 = 6;
+
+/// A button becomes Active when the mouse is pressed over it, and emits Clicked
+/// when the mouse is released over it. Releasing it outside cancels the click.
+/// When it has the focus, Space or Enter emit Clicked too.
+pub fn update_button(widget: &mut Widget, data: &UpdateData) -> Option<Event> {
+    if widget.focused && !data.key_repeat {
+        if let KeyboardStatus::KeyDown(Key::Space | Key::Enter) = data.keyboard_status {
+            return widget.command.map(Event::Clicked);
+        }
+    }
+    let inside = widget.space.is_inside(data.mouse_position.0, data.mouse_position.1);
+    let mut event = None;
+    widget.status = match (&widget.status, data.mouse_status) {
+        (WidgetStatus::Active, MouseStatus::Released) => {
+            if inside {
+                event = widget.command.map(Event::Clicked);
+                WidgetStatus::Hover
+            } else {
+                WidgetStatus::Inactive
+            }
+        },
+        (WidgetStatus::Active, _) => WidgetStatus::Active,
+        (_, MouseStatus::Pressed) if inside => WidgetStatus::Active,
+        _ if inside => WidgetStatus::Hover,
+        _ => WidgetStatus::Inactive,
+    };
+    event
+}
+
+/// Draws the button with its caption centered, and its accelerator, if any, in the bottom right corner
+pub fn paint_button(widget: &Widget, surface: &mut dyn Surface) {
+    let (x1, y1, x2, y2) = widget.space.prop_coords;
+    match widget.theme.style {
+        WidgetStyle::Classic => surface.draw_rectangle(x1, y1, x2, y2, Some(&widget.color_border), Some(&widget.color_bg)),
+        WidgetStyle::Modern => surface.draw_rounded_rectangle(x1, y1, x2, y2, RADIUS, Some(&widget.color_border), Some(&widget.color_bg)),
+    }
+
+    let space = widget.space.shrink(PADDING, PADDING);
     let (x1, y1, x2, y2) = space.prop_coords;
-    surface.draw_rectangle(x1, y1, x2, y2, Some(&color_border), Some(&color_bg))
-}
\ No newline at end of file
+    let (px1, py1, px2, py2) = space.pixel_coords;
+    let (width, height) = (px2.saturating_sub(px1) as f32, py2.saturating_sub(py1) as f32);
+    if width <= 0_f32 || height <= 0_f32 {
+        return;
+    }
+    if let WidgetInfo::Text(caption) = &widget.info {
+        let format = TextFormat{
+            horizontal: HorizontalAlign::Center,
+            vertical: VerticalAlign::Middle,
+            ..widget.theme.text_format
+        };
+        let layout = TextLayout::new(caption, width, height, &format, surface);
+        layout.draw(surface, x1, y1, x2, y2, &widget.text_color());
+    }
+    if let Some(accelerator) = &widget.accelerator {
+        let style = widget.theme.text_format.style;
+        let format = TextFormat{
+            style: TextStyle{size: style.size * 0.75_f32, ..style},
+            horizontal: HorizontalAlign::Right,
+            vertical: VerticalAlign::Bottom,
+            ..widget.theme.text_format
+        };
+        let color = widget.text_color();
+        let layout = TextLayout::new(accelerator, width, height, &format, surface);
+        layout.draw(surface, x1, y1, x2, y2, &Color{a: color.a * 0.6_f32, ..color});
+    }
+}
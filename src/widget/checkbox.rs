@@ -0,0 +1,125 @@
+/*
+Copyright 2021 Gonzalo Fernández Hernández
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+use crate::{Surface, UpdateData, MouseStatus, KeyboardStatus, Key, Event, Color, ValueState, WidgetStyle};
+use crate::{TextLayout, TextFormat, HorizontalAlign, VerticalAlign};
+use crate::widget::{Widget, WidgetInfo, WidgetStatus};
+
+/// Space in pixels between the border of the widget and its parts
+pub const PADDING: f32 = 4_f32;
+
+/// Space in pixels between the box and the caption
+pub const GAP: f32 = 6_f32;
+
+/// Maximum side in pixels of the box
+const BOX_SIZE: f32 = 18_f32;
+
+/// Shared by checkboxes and toggles: clicking the widget, or pressing Space while it
+/// has the focus, changes its state and emits StateChanged.
+pub fn update_check(widget: &mut Widget, data: &UpdateData) -> Option<Event> {
+    let inside = widget.space.is_inside(data.mouse_position.0, data.mouse_position.1);
+    let mut toggle = widget.focused && !data.key_repeat && data.keyboard_status == KeyboardStatus::KeyDown(Key::Space);
+    widget.status = match (&widget.status, data.mouse_status) {
+        (WidgetStatus::Active, MouseStatus::Released) => {
+            toggle |= inside;
+            if inside { WidgetStatus::Hover } else { WidgetStatus::Inactive }
+        },
+        (WidgetStatus::Active, _) => WidgetStatus::Active,
+        (_, MouseStatus::Pressed) if inside => WidgetStatus::Active,
+        _ if inside => WidgetStatus::Hover,
+        _ => WidgetStatus::Inactive,
+    };
+    match &mut widget.info {
+        WidgetInfo::Check(checked, _) if toggle => {
+            *checked = !*checked;
+            widget.value.map(|value| Event::StateChanged(value, *checked))
+        },
+        _ => None,
+    }
+}
+
+/// Draws the caption of a checkbox or a toggle, from a position in pixels to the right of the widget
+pub fn paint_caption(widget: &Widget, surface: &mut dyn Surface, left: f32) {
+    let caption = match &widget.info {
+        WidgetInfo::Check(_, caption) if !caption.is_empty() => caption,
+        _ => return,
+    };
+    let (_, py1, px2, py2) = widget.space.pixel_coords;
+    let right = px2 as f32 - PADDING;
+    if right <= left {
+        return;
+    }
+    let format = TextFormat{
+        horizontal: HorizontalAlign::Left,
+        vertical: VerticalAlign::Middle,
+        ..widget.theme.text_format
+    };
+    let (x1, y1) = widget.space.to_prop(left, py1 as f32);
+    let (x2, y2) = widget.space.to_prop(right, py2 as f32);
+    let layout = TextLayout::new(caption, right - left, (py2 - py1) as f32, &format, surface);
+    layout.draw(surface, x1, y1, x2, y2, &widget.text_color());
+}
+
+/// Draws a box, with a tick when checked, and the caption at its right
+pub fn paint_checkbox(widget: &Widget, surface: &mut dyn Surface) {
+    let checked = matches!(widget.info, WidgetInfo::Check(true, _));
+    let (px1, py1, _, py2) = widget.space.pixel_coords;
+    let size = ((py2 - py1) as f32 - 2_f32 * PADDING).min(BOX_SIZE);
+    if size <= 0_f32 {
+        return;
+    }
+    let left = px1 as f32 + PADDING;
+    let top = (py1 + py2) as f32 * 0.5_f32 - size * 0.5_f32;
+    let (x1, y1) = widget.space.to_prop(left, top);
+    let (x2, y2) = widget.space.to_prop(left + size, top + size);
+
+    let mark = widget.state_color(widget.theme.text);
+    let border = match widget.state {
+        ValueState::Correct | ValueState::Wrong => mark,
+        _ => widget.color_border,
+    };
+    let tick = match widget.theme.style {
+        WidgetStyle::Classic => {
+            surface.draw_rectangle(x1, y1, x2, y2, Some(&border), Some(&widget.color_bg));
+            mark
+        },
+        WidgetStyle::Modern if checked => {
+            surface.draw_rounded_rectangle(x1, y1, x2, y2, (size * 0.25_f32) as u32, Some(&mark), Some(&mark));
+            widget.theme.inactive
+        },
+        WidgetStyle::Modern => {
+            surface.draw_rounded_rectangle(x1, y1, x2, y2, (size * 0.25_f32) as u32, Some(&border), Some(&widget.color_bg));
+            mark
+        },
+    };
+    if checked {
+        paint_tick(widget, surface, left, top, size, &tick);
+    }
+    paint_caption(widget, surface, left + size + GAP);
+}
+
+/// Draws a tick two pixels wide inside a box given in pixels
+fn paint_tick(widget: &Widget, surface: &mut dyn Surface, left: f32, top: f32, size: f32, color: &Color) {
+    let points = [(0.22_f32, 0.5_f32), (0.42_f32, 0.72_f32), (0.78_f32, 0.3_f32)];
+    for offset in 0..2 {
+        let point = |i: usize| widget.space.to_prop(left + points[i].0 * size, top + points[i].1 * size + offset as f32);
+        for i in 0..2 {
+            let ((x1, y1), (x2, y2)) = (point(i), point(i + 1));
+            surface.draw_line(x1, y1, x2, y2, color);
+        }
+    }
+}
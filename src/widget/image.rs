@@ -0,0 +1,105 @@
+/*
+Copyright 2021 Gonzalo Fernández Hernández
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+use crate::{Surface, ImageId};
+use crate::widget::{Widget, WidgetInfo};
+
+/// How an image fills the space of its widget
+///     * Fit: As big as possible inside the space, keeping the aspect ratio (letterbox)
+///     * Fill: Covers all the space keeping the aspect ratio; what falls out is cropped
+///     * Stretch: Covers all the space, without keeping the aspect ratio
+///     * Tile: Repeated at its own size from the top left corner
+///     * Center: At its own size in the center; what falls out is cropped
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ImageMode {
+    Fit,
+    Fill,
+    Stretch,
+    Tile,
+    Center,
+}
+
+/// Draws the image in its mode, or a placeholder while the image is not loaded.
+/// The size is computed from the space every time, so the aspect ratio is kept when it is resized.
+pub fn paint_image(widget: &Widget, surface: &mut dyn Surface) {
+    let (image, mode) = match &widget.info {
+        WidgetInfo::Image(image, mode) => (*image, *mode),
+        _ => return,
+    };
+    let (px1, py1, px2, py2) = widget.space.pixel_coords;
+    let area = (px1 as f32, py1 as f32, px2 as f32, py2 as f32);
+    let (width, height) = (area.2 - area.0, area.3 - area.1);
+    let (img_width, img_height) = match surface.image_size(image) {
+        Some((w, h)) if w > 0 && h > 0 => (w as f32, h as f32),
+        _ => return paint_placeholder(widget, surface),
+    };
+
+    let centered = |w: f32, h: f32| {
+        let left = area.0 + (width - w) * 0.5_f32;
+        let top = area.1 + (height - h) * 0.5_f32;
+        (left, top, left + w, top + h)
+    };
+    match mode {
+        ImageMode::Stretch => paint_clipped(widget, surface, image, area, area),
+        ImageMode::Fit => {
+            let scale = (width / img_width).min(height / img_height);
+            paint_clipped(widget, surface, image, centered(img_width * scale, img_height * scale), area);
+        },
+        ImageMode::Fill => {
+            let scale = (width / img_width).max(height / img_height);
+            paint_clipped(widget, surface, image, centered(img_width * scale, img_height * scale), area);
+        },
+        ImageMode::Center => paint_clipped(widget, surface, image, centered(img_width, img_height), area),
+        ImageMode::Tile => {
+            let mut top = area.1;
+            while top < area.3 {
+                let mut left = area.0;
+                while left < area.2 {
+                    paint_clipped(widget, surface, image, (left, top, left + img_width, top + img_height), area);
+                    left += img_width;
+                }
+                top += img_height;
+            }
+        },
+    }
+}
+
+/// Draws the part of an image, placed at a rectangle in pixels, that falls inside a clip rectangle in pixels
+fn paint_clipped(widget: &Widget, surface: &mut dyn Surface, image: ImageId, place: (f32, f32, f32, f32), clip: (f32, f32, f32, f32)) {
+    let (x1, y1) = (place.0.max(clip.0), place.1.max(clip.1));
+    let (x2, y2) = (place.2.min(clip.2), place.3.min(clip.3));
+    let (width, height) = (place.2 - place.0, place.3 - place.1);
+    if x2 <= x1 || y2 <= y1 || width <= 0_f32 || height <= 0_f32 {
+        return;
+    }
+    let region = (
+        (x1 - place.0) / width,
+        (y1 - place.1) / height,
+        (x2 - place.0) / width,
+        (y2 - place.1) / height);
+    let (x1, y1) = widget.space.to_prop(x1, y1);
+    let (x2, y2) = widget.space.to_prop(x2, y2);
+    surface.draw_image_region(image, x1, y1, x2, y2, region);
+}
+
+/// Draws a box crossed by its diagonals
+fn paint_placeholder(widget: &Widget, surface: &mut dyn Surface) {
+    let (x1, y1, x2, y2) = widget.space.prop_coords;
+    surface.draw_rectangle(x1, y1, x2, y2, Some(&widget.color_border), Some(&widget.theme.active));
+    surface.draw_line(x1, y1, x2, y2, &widget.color_border);
+    surface.draw_line(x1, y2, x2, y1, &widget.color_border);
+}
@@ -0,0 +1,29 @@
+/*
+Copyright 2021 Gonzalo Fernández Hernández
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+use crate::{Surface, TextLayout};
+use crate::widget::{Widget, WidgetInfo};
+
+/// Draws the text of the label with the format of the theme
+pub fn paint_label(widget: &Widget, surface: &mut dyn Surface) {
+    if let WidgetInfo::Text(text) = &widget.info {
+        let (x1, y1, x2, y2) = widget.space.prop_coords;
+        let (px1, py1, px2, py2) = widget.space.pixel_coords;
+        let layout = TextLayout::new(text, (px2 - px1) as f32, (py2 - py1) as f32, &widget.theme.text_format, surface);
+        layout.draw(surface, x1, y1, x2, y2, &widget.text_color());
+    }
+}
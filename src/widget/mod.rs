@@ -15,23 +15,46 @@ limitations under the License.
 */
 
 mod button;
+mod checkbox;
+mod image;
+mod label;
+mod slider;
+mod spin_box;
+mod text_input;
+mod toggle;
 
 use crate::Space;
-use crate::UpdateData;
-use crate::Surface;
+use crate::{UpdateData, Key, Chord};
+use crate::{Surface, ImageId};
 use crate::Color;
-use crate::Theme;
+use crate::{Theme, WidgetStyle};
+use crate::{CommandId, ValueId, Event, Message, ValueState};
 
-use button::paint_button;
+use text_input::TextEdit;
+use spin_box::SpinBox;
+use slider::Slider;
+pub use slider::{SliderOptions, SliderEmit};
+pub use image::ImageMode;
+
+/// Width in pixels of the ring drawn around the focused widget
+const FOCUS_RING: u32 = 2;
+
+/// Radius in pixels of the corners of the panels in the Modern style
+const PANEL_RADIUS: u32 = 8;
 
 enum WidgetInfo {
     None,
     Text(String),
     Number(i32),
     Float(f32),
-    Image()
+    Image(ImageId, ImageMode),
+    Edit(TextEdit),
+    Check(bool, String),
+    Spin(SpinBox),
+    Slider(Slider),
 }
 
+#[derive(PartialEq)]
 enum WidgetStatus {
     Inactive,
     Active,
@@ -47,8 +70,14 @@ pub struct Widget {
     color_bg: Color,
     color_border: Color,
     status: WidgetStatus,
-    update_fn: Option<fn(&UpdateData)>,
-    paint_fn: Option<fn(&Space, &mut dyn Surface, color_fg: &Color, color_bg: &Color, color_border: &Color)>,
+    focusable: bool,
+    focused: bool,
+    command: Option<CommandId>,
+    accelerator: Option<String>,
+    value: Option<ValueId>,
+    state: ValueState,
+    update_fn: Option<fn(&mut Widget, &UpdateData) -> Option<Event>>,
+    paint_fn: Option<fn(&Widget, &mut dyn Surface)>,
 }
 
 impl std::fmt::Debug for Widget {
@@ -58,30 +87,263 @@ impl std::fmt::Debug for Widget {
 }
 
 impl Widget {
-    pub fn new_button(signal: u32, theme: Theme) -> Self {
+    /// Creates a widget with the common fields initialized
+    fn new(info: WidgetInfo, theme: Theme) -> Self {
         Widget{
             space: Space::new(1, 1), 
             theme, 
-            info: WidgetInfo::None,
+            info,
             color_fg: theme.border,
             color_bg: theme.inactive,
             color_border: theme.border,
             status: WidgetStatus::Inactive,
+            focusable: false,
+            focused: false,
+            command: None,
+            accelerator: None,
+            value: None,
+            state: ValueState::Enabled,
             update_fn: None, 
-            paint_fn: Some(button::paint_button)
+            paint_fn: None,
         }
     }
 
+    pub fn new_button(signal: CommandId, theme: Theme) -> Self {
+        let mut widget = Widget::new(WidgetInfo::None, theme);
+        widget.command = Some(signal);
+        widget.focusable = true;
+        widget.update_fn = Some(button::update_button);
+        widget.paint_fn = Some(button::paint_button);
+        widget
+    }
+
+    /// Creates a button with a caption
+    /// params:
+    ///     * signal: The command sent with Clicked
+    ///     * caption: The text shown in the button
+    ///     * theme: Colors and text format
+    pub fn new_text_button(signal: CommandId, caption: &str, theme: Theme) -> Self {
+        let mut widget = Widget::new_button(signal, theme);
+        widget.info = WidgetInfo::Text(caption.to_string());
+        widget
+    }
+
+    /// Creates a checkbox: a box with a tick when checked, and a caption
+    /// params:
+    ///     * value: The value shown. It is sent with StateChanged, and set with ValueChangedState messages.
+    ///     * caption: The text at the right of the box
+    ///     * checked: The state at start
+    ///     * theme: Colors and style
+    pub fn new_checkbox(value: ValueId, caption: &str, checked: bool, theme: Theme) -> Self {
+        let mut widget = Widget::new(WidgetInfo::Check(checked, caption.to_string()), theme);
+        widget.value = Some(value);
+        widget.focusable = true;
+        widget.update_fn = Some(checkbox::update_check);
+        widget.paint_fn = Some(checkbox::paint_checkbox);
+        widget
+    }
+
+    /// Creates a toggle switch: a knob that slides in a track, and a caption. It works as a checkbox.
+    /// params:
+    ///     * value: The value shown. It is sent with StateChanged, and set with ValueChangedState messages.
+    ///     * caption: The text at the right of the switch
+    ///     * on: The state at start
+    ///     * theme: Colors and style
+    pub fn new_toggle(value: ValueId, caption: &str, on: bool, theme: Theme) -> Self {
+        let mut widget = Widget::new_checkbox(value, caption, on, theme);
+        widget.paint_fn = Some(toggle::paint_toggle);
+        widget
+    }
+
+    /// Creates a spin box for integers
+    /// params:
+    ///     * value: The value shown. It is sent with NumberInserted, and set with ValueChangedNumber messages.
+    ///     * number: The number at start
+    ///     * min: The lowest number allowed
+    ///     * max: The highest number allowed
+    ///     * step: The change of the arrows, the wheel and the keys
+    ///     * theme: Colors and style
+    pub fn new_spin_box(value: ValueId, number: i32, min: i32, max: i32, step: i32, theme: Theme) -> Self {
+        let spin = SpinBox::new(number as f64, min as f64, max as f64, step as f64, 0, false);
+        Widget::new_spin_widget(value, spin, theme)
+    }
+
+    /// Creates a spin box for decimal numbers
+    /// params:
+    ///     * value: The value shown. It is sent with FloatInserted, and set with ValueChangedFloat messages.
+    ///     * number: The number at start
+    ///     * min: The lowest number allowed
+    ///     * max: The highest number allowed
+    ///     * step: The change of the arrows, the wheel and the keys
+    ///     * decimals: The decimal places shown; the number is rounded to them
+    ///     * theme: Colors and style
+    pub fn new_float_spin_box(value: ValueId, number: f32, min: f32, max: f32, step: f32, decimals: usize, theme: Theme) -> Self {
+        let spin = SpinBox::new(number as f64, min as f64, max as f64, step as f64, decimals, true);
+        Widget::new_spin_widget(value, spin, theme)
+    }
+
+    fn new_spin_widget(value: ValueId, spin: SpinBox, theme: Theme) -> Self {
+        let mut widget = Widget::new(WidgetInfo::Spin(spin), theme);
+        widget.value = Some(value);
+        widget.focusable = true;
+        widget.update_fn = Some(spin_box::update_spin_box);
+        widget.paint_fn = Some(spin_box::paint_spin_box);
+        widget
+    }
+
+    /// Creates a slider for integers
+    /// params:
+    ///     * value: The value shown. It is sent with NumberInserted, and set with ValueChangedNumber messages.
+    ///     * number: The number at start
+    ///     * min: The lowest number allowed
+    ///     * max: The highest number allowed
+    ///     * options: Direction, step, tick marks and when the events are emitted
+    ///     * theme: Colors and style
+    pub fn new_slider(value: ValueId, number: i32, min: i32, max: i32, options: SliderOptions, theme: Theme) -> Self {
+        let slider = Slider::new(options, min as f64, max as f64, vec![number as f64], vec![value], false);
+        Widget::new_slider_widget(value, slider, theme)
+    }
+
+    /// Creates a slider for decimal numbers
+    /// params:
+    ///     * value: The value shown. It is sent with FloatInserted, and set with ValueChangedFloat messages.
+    ///     * number: The number at start
+    ///     * min: The lowest number allowed
+    ///     * max: The highest number allowed
+    ///     * options: Direction, step, tick marks and when the events are emitted
+    ///     * theme: Colors and style
+    pub fn new_float_slider(value: ValueId, number: f32, min: f32, max: f32, options: SliderOptions, theme: Theme) -> Self {
+        let slider = Slider::new(options, min as f64, max as f64, vec![number as f64], vec![value], true);
+        Widget::new_slider_widget(value, slider, theme)
+    }
+
+    /// Creates a slider with two handles that choose a range of integers
+    /// params:
+    ///     * values: The values of the low and the high handles
+    ///     * numbers: The low and the high numbers at start
+    ///     * min: The lowest number allowed
+    ///     * max: The highest number allowed
+    ///     * options: Direction, step, tick marks and when the events are emitted
+    ///     * theme: Colors and style
+    pub fn new_range_slider(values: (ValueId, ValueId), numbers: (i32, i32), min: i32, max: i32, options: SliderOptions, theme: Theme) -> Self {
+        let slider = Slider::new(options, min as f64, max as f64,
+            vec![numbers.0 as f64, numbers.1 as f64], vec![values.0, values.1], false);
+        Widget::new_slider_widget(values.0, slider, theme)
+    }
+
+    /// Creates a slider with two handles that choose a range of decimal numbers
+    /// params:
+    ///     * values: The values of the low and the high handles
+    ///     * numbers: The low and the high numbers at start
+    ///     * min: The lowest number allowed
+    ///     * max: The highest number allowed
+    ///     * options: Direction, step, tick marks and when the events are emitted
+    ///     * theme: Colors and style
+    pub fn new_float_range_slider(values: (ValueId, ValueId), numbers: (f32, f32), min: f32, max: f32, options: SliderOptions, theme: Theme) -> Self {
+        let slider = Slider::new(options, min as f64, max as f64,
+            vec![numbers.0 as f64, numbers.1 as f64], vec![values.0, values.1], true);
+        Widget::new_slider_widget(values.0, slider, theme)
+    }
+
+    fn new_slider_widget(value: ValueId, slider: Slider, theme: Theme) -> Self {
+        let mut widget = Widget::new(WidgetInfo::Slider(slider), theme);
+        widget.value = Some(value);
+        widget.focusable = true;
+        widget.update_fn = Some(slider::update_slider);
+        widget.paint_fn = Some(slider::paint_slider);
+        widget
+    }
+
+    /// Creates a widget that shows an image. A placeholder is shown while the image is not loaded.
+    /// params:
+    ///     * image: The image, loaded in the surface
+    ///     * mode: How the image fills the space of the widget
+    ///     * theme: Colors of the placeholder
+    pub fn new_image(image: ImageId, mode: ImageMode, theme: Theme) -> Self {
+        let mut widget = Widget::new(WidgetInfo::Image(image, mode), theme);
+        widget.paint_fn = Some(image::paint_image);
+        widget
+    }
+
+    /// Creates a label that shows a text
+    /// params:
+    ///     * text: The text shown at start
+    ///     * value: If given, the text changes with ValueChangedText messages for this value
+    ///     * theme: Colors and text format
+    pub fn new_label(text: &str, value: Option<ValueId>, theme: Theme) -> Self {
+        let mut widget = Widget::new(WidgetInfo::Text(text.to_string()), theme);
+        widget.value = value;
+        widget.paint_fn = Some(label::paint_label);
+        widget
+    }
+
+    /// Creates a single line text input
+    /// params:
+    ///     * value: The value edited. It is sent with TextInserted, and set with ValueChangedText messages.
+    ///     * text: The text at start
+    ///     * theme: Colors and text format
+    pub fn new_text_input(value: ValueId, text: &str, theme: Theme) -> Self {
+        let mut widget = Widget::new(WidgetInfo::Edit(TextEdit::new(text)), theme);
+        widget.value = Some(value);
+        widget.focusable = true;
+        widget.update_fn = Some(text_input::update_text_input);
+        widget.paint_fn = Some(text_input::paint_text_input);
+        widget
+    }
+
     pub fn set_space(&mut self, space: Space) {
         self.space = space;
     }
 
-    pub fn update(&mut self, data: &UpdateData) {
-        self.status = if self.space.is_inside(data.mouse_position.0, data.mouse_position.1) {
-            WidgetStatus::Hover
-        } else { 
-            WidgetStatus::Inactive
-        };
+    /// Shows the accelerator of the command of the widget, like "Ctrl+S".
+    /// The accelerator itself is added to the screen or the window.
+    pub fn set_accelerator(&mut self, chords: &[Chord]) {
+        self.accelerator = Some(Chord::sequence_text(chords));
+    }
+
+    pub fn get_space(&self) -> &Space {
+        &self.space
+    }
+
+    /// Whether a position in pixels is inside the widget
+    pub fn is_inside(&self, x: u32, y: u32) -> bool {
+        self.space.is_inside(x, y)
+    }
+
+    /// Whether the widget can take the keyboard focus now
+    pub fn can_focus(&self) -> bool {
+        self.focusable && !matches!(self.state, ValueState::Disabled | ValueState::Hidden)
+    }
+
+    /// Whether the widget uses a key itself, so the screen must not use it to move the focus
+    pub fn captures_key(&self, key: Key) -> bool {
+        match &self.info {
+            WidgetInfo::Edit(_) => matches!(key, Key::Left | Key::Right),
+            WidgetInfo::Spin(spin) => spin.captures_key(key),
+            WidgetInfo::Slider(slider) => slider.captures_key(key),
+            _ => false,
+        }
+    }
+
+    /// Gives or takes the keyboard focus. The screen decides which widget has it.
+    pub fn set_focus(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    /// Updates the status of the widget with the input, and returns the event it produces, if any.
+    /// A widget disabled by the app ignores the input.
+    pub fn update(&mut self, data: &UpdateData) -> Option<Event> {
+        let mut event = None;
+        if matches!(self.state, ValueState::Disabled | ValueState::Hidden) {
+            self.status = WidgetStatus::Disabled;
+        } else {
+            if self.status == WidgetStatus::Disabled {
+                self.status = WidgetStatus::Inactive;
+            }
+            if let Some(function) = self.update_fn {
+                event = function(self, data);
+            }
+        }
 
         match self.status {
             WidgetStatus::Inactive => {
@@ -105,12 +367,100 @@ impl Widget {
                 self.color_border.approach(self.theme.border);
             },
         }
+        event
+    }
+
+    /// Applies a message from the app to this widget, if the message refers to it
+    pub fn process_message(&mut self, message: &Message) {
+        match (&mut self.info, message) {
+            // The handles of a range slider have their own values
+            (WidgetInfo::Slider(slider), Message::ValueChangedNumber(value, number, state)) => {
+                if slider.set_value_of(*value, *number as f64) {
+                    self.state = *state;
+                }
+                return;
+            },
+            (WidgetInfo::Slider(slider), Message::ValueChangedFloat(value, number, state)) => {
+                if slider.set_value_of(*value, *number as f64) {
+                    self.state = *state;
+                }
+                return;
+            },
+            _ => (),
+        }
+        match message {
+            Message::CommandAvailable(command, state) if self.command == Some(*command) => {
+                self.state = (*state).into();
+            },
+            Message::ValueChangedState(value, checked, state) if self.value == Some(*value) => {
+                if let WidgetInfo::Check(current, _) = &mut self.info {
+                    *current = *checked;
+                }
+                self.state = *state;
+            },
+            Message::ValueChangedNumber(value, number, state) if self.value == Some(*value) => {
+                if let WidgetInfo::Spin(spin) = &mut self.info {
+                    spin.set_number(*number as f64);
+                }
+                self.state = *state;
+            },
+            Message::ValueChangedFloat(value, number, state) if self.value == Some(*value) => {
+                if let WidgetInfo::Spin(spin) = &mut self.info {
+                    spin.set_number(*number as f64);
+                }
+                self.state = *state;
+            },
+            Message::ValueChangedText(value, text, state) if self.value == Some(*value) => {
+                // Other kinds of widget keep their data, the text only changes their state
+                match &mut self.info {
+                    WidgetInfo::Edit(edit) => edit.set_text(text),
+                    WidgetInfo::Text(current) => *current = text.clone(),
+                    _ => (),
+                }
+                self.state = *state;
+            },
+            _ => (),
+        }
     }
 
     pub fn paint(&self, surface: &mut dyn Surface) {
+        if self.state == ValueState::Hidden {
+            return;
+        }
         if let Some(function) = self.paint_fn {
-            function(&self.space, surface, &self.theme.active, &self.color_bg, &self.color_border);
+            function(self, surface);
         }       
+        if self.focused {
+            self.paint_focus_ring(surface);
+        }
+    }
+
+    /// Draws a ring of FOCUS_RING pixels inside the border of the widget
+    fn paint_focus_ring(&self, surface: &mut dyn Surface) {
+        let (x1, y1, x2, y2) = self.space.prop_coords;
+        let (px1, py1, px2, py2) = self.space.pixel_coords;
+        let pixel_x = (x2 - x1) / (px2 - px1).max(1) as f32;
+        let pixel_y = (y2 - y1) / (py2 - py1).max(1) as f32;
+        for i in 1..=FOCUS_RING {
+            let (dx, dy) = (pixel_x * i as f32, pixel_y * i as f32);
+            surface.draw_rectangle(x1 + dx, y1 + dy, x2 - dx, y2 - dy, Some(&self.theme.focus), None);
+        }
+    }
+
+    /// Color for the text of the widget, depending on the state given by the app
+    fn text_color(&self) -> Color {
+        self.state_color(self.theme.text)
+    }
+
+    /// Color for a part of the widget that shows the state given by the app:
+    /// right or wrong when Correct or Wrong, half transparent when Disabled, and the color given otherwise
+    fn state_color(&self, color: Color) -> Color {
+        match self.state {
+            ValueState::Correct => self.theme.right,
+            ValueState::Wrong => self.theme.wrong,
+            ValueState::Disabled => Color{a: color.a * 0.5_f32, ..color},
+            _ => color,
+        }
     }
 }
 
@@ -119,21 +469,34 @@ pub struct Panel {
     space: Space,
     color: Color,
     border_color: Option<Color>,
+    style: WidgetStyle,
 }
 
 impl Panel {
+    /// Creates a panel with square corners
     pub fn new(color: Color, border_color: Option<Color>) -> Self {
-        Panel{space: Space::new(1, 1), color, border_color}
+        Panel{space: Space::new(1, 1), color, border_color, style: WidgetStyle::Classic}
+    }
+
+    /// Creates a panel with the background, border and style of a theme
+    pub fn new_themed(theme: Theme) -> Self {
+        Panel{space: Space::new(1, 1), color: theme.background, border_color: Some(theme.border), style: theme.style}
     }
 
     pub fn set_space(&mut self, space: Space) {
         self.space = space;
     }
 
+    pub fn get_space(&self) -> &Space {
+        &self.space
+    }
+
     pub fn paint(&self, surface: &mut dyn Surface) {
-        surface.draw_rectangle(
-            self.space.prop_coords.0, self.space.prop_coords.1, 
-            self.space.prop_coords.2, self.space.prop_coords.3, 
-            self.border_color.as_ref(), Some(&self.color));       
+        let (x1, y1, x2, y2) = self.space.prop_coords;
+        match self.style {
+            WidgetStyle::Classic => surface.draw_rectangle(x1, y1, x2, y2, self.border_color.as_ref(), Some(&self.color)),
+            WidgetStyle::Modern => surface.draw_rounded_rectangle(x1, y1, x2, y2, PANEL_RADIUS,
+                self.border_color.as_ref(), Some(&self.color)),
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,405 @@
+/*
+Copyright 2021 Gonzalo Fernández Hernández
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+use crate::{Surface, UpdateData, MouseStatus, KeyboardStatus, Key, Event, ValueId, WidgetStyle, Direction};
+use crate::widget::{Widget, WidgetInfo, WidgetStatus};
+
+/// Space in pixels between the border of the widget and the ends of the track
+const PADDING: f32 = 4_f32;
+
+/// Width in pixels of the track
+const TRACK: f32 = 4_f32;
+
+/// Side in pixels of a handle
+const HANDLE: f32 = 14_f32;
+
+/// Length in pixels of the tick marks
+const TICK: f32 = 4_f32;
+
+/// Steps changed with PageUp and PageDown
+const PAGE_STEPS: f64 = 10_f64;
+
+/// Parts of the track moved with the keys when there is no step
+const KEY_FRACTION: f64 = 0.01_f64;
+
+/// When a slider emits its events while a handle is dragged
+///     * Continuous: Every time the value changes
+///     * OnRelease: Once, when the handle is released
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SliderEmit {
+    Continuous,
+    OnRelease,
+}
+
+/// Options of a slider
+///     * direction: Horizontal sliders grow to the right, and vertical sliders grow up
+///     * step: The values are snapped to min plus a multiple of the step. 0 does not snap floats.
+///     * ticks: Distance between tick marks, if they are drawn
+///     * emit: When the events are emitted while dragging
+#[derive(Debug, Copy, Clone)]
+pub struct SliderOptions {
+    pub direction: Direction,
+    pub step: f32,
+    pub ticks: Option<f32>,
+    pub emit: SliderEmit,
+}
+
+impl Default for SliderOptions {
+    fn default() -> Self {
+        SliderOptions{direction: Direction::Horizontal, step: 0_f32, ticks: None, emit: SliderEmit::Continuous}
+    }
+}
+
+/// A value, or a range of values, chosen by moving handles over a track
+///     * values: The value of each handle; a range has two, low and high
+///     * ids: The ValueId of each handle
+///     * float: Whether the events are FloatInserted instead of NumberInserted
+///     * handle: The handle moved with the keys: the last one used, or the one chosen with Space
+///     * dragging: Whether the handle is being dragged. The mouse is captured until it is released.
+///     * pending: Whether the value changed during the drag and was not emitted yet
+pub struct Slider {
+    options: SliderOptions,
+    min: f64,
+    max: f64,
+    values: Vec<f64>,
+    ids: Vec<ValueId>,
+    float: bool,
+    handle: usize,
+    dragging: bool,
+    pending: bool,
+}
+
+/// Position of the track in pixels, along the direction of the slider
+///     * start: Where the minimum is
+///     * length: Pixels from the minimum to the maximum; negative when the slider grows up
+///     * center: Center of the track, across the direction of the slider
+struct Track {
+    horizontal: bool,
+    start: f32,
+    length: f32,
+    center: f32,
+}
+
+impl Track {
+    fn new(widget: &Widget, direction: Direction) -> Self {
+        let (px1, py1, px2, py2) = widget.space.pixel_coords;
+        let (px1, py1, px2, py2) = (px1 as f32, py1 as f32, px2 as f32, py2 as f32);
+        let margin = PADDING + HANDLE * 0.5_f32;
+        match direction {
+            Direction::Horizontal => Track{horizontal: true, start: px1 + margin, length: (px2 - px1 - 2_f32 * margin).max(0_f32), center: (py1 + py2) * 0.5_f32},
+            Direction::Vertical => Track{horizontal: false, start: py2 - margin, length: -(py2 - py1 - 2_f32 * margin).max(0_f32), center: (px1 + px2) * 0.5_f32},
+        }
+    }
+
+    /// Position in pixels of the window of a point given along and across the track
+    fn point(&self, along: f32, across: f32) -> (f32, f32) {
+        if self.horizontal { (along, self.center + across) } else { (self.center + across, along) }
+    }
+
+    /// Position along the track of a proportion between the minimum (0) and the maximum (1)
+    fn along(&self, proportion: f32) -> f32 {
+        self.start + proportion * self.length
+    }
+
+    /// Proportion of the track at a position of the mouse
+    fn proportion(&self, x: u32, y: u32) -> f32 {
+        let along = if self.horizontal { x as f32 } else { y as f32 };
+        if self.length == 0_f32 { 0_f32 } else { ((along - self.start) / self.length).clamp(0_f32, 1_f32) }
+    }
+}
+
+impl Slider {
+    pub fn new(options: SliderOptions, min: f64, max: f64, values: Vec<f64>, ids: Vec<ValueId>, float: bool) -> Self {
+        let mut slider = Slider{
+            options, 
+            min: min.min(max), 
+            max: max.max(min), 
+            values: values.clone(), 
+            ids, 
+            float, 
+            handle: 0, 
+            dragging: false, 
+            pending: false,
+        };
+        for (i, value) in values.into_iter().enumerate() {
+            slider.set_value(i, value);
+        }
+        slider
+    }
+
+    /// Sets the value of the handle with an id
+    /// returns:
+    ///     Whether the id is one of the handles
+    pub fn set_value_of(&mut self, id: ValueId, value: f64) -> bool {
+        match self.ids.iter().position(|x| *x == id) {
+            Some(handle) => {
+                self.set_value(handle, value);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Whether the slider uses a key itself: the arrows along its direction
+    pub fn captures_key(&self, key: Key) -> bool {
+        match self.options.direction {
+            Direction::Horizontal => matches!(key, Key::Left | Key::Right),
+            Direction::Vertical => matches!(key, Key::Up | Key::Down),
+        }
+    }
+
+    /// Sets the value of a handle, snapped to the step and kept between the limits and the other handle
+    /// returns:
+    ///     Whether the value changed
+    fn set_value(&mut self, handle: usize, value: f64) -> bool {
+        let step = self.step();
+        let mut value = value.max(self.min).min(self.max);
+        if step > 0_f64 {
+            value = (self.min + ((value - self.min) / step).round() * step).min(self.max);
+        }
+        if handle > 0 {
+            value = value.max(self.values[handle - 1]);
+        }
+        if handle + 1 < self.values.len() {
+            value = value.min(self.values[handle + 1]);
+        }
+        let changed = value != self.values[handle];
+        self.values[handle] = value;
+        changed
+    }
+
+    /// The step used to snap: integers use whole steps
+    fn step(&self) -> f64 {
+        let step = self.options.step as f64;
+        if self.float { step.max(0_f64) } else { step.round().max(1_f64) }
+    }
+
+    /// The step used by the keys
+    fn key_step(&self) -> f64 {
+        match self.step() {
+            step if step > 0_f64 => step,
+            _ => (self.max - self.min) * KEY_FRACTION,
+        }
+    }
+
+    fn proportion(&self, handle: usize) -> f32 {
+        if self.max > self.min { ((self.values[handle] - self.min) / (self.max - self.min)) as f32 } else { 0_f32 }
+    }
+
+    /// Proportions of the track where the tick marks are drawn
+    fn ticks(&self) -> Vec<f32> {
+        let interval = match self.options.ticks {
+            Some(interval) if interval > 0_f32 && self.max > self.min => interval as f64,
+            _ => return Vec::new(),
+        };
+        let count = ((self.max - self.min) / interval).floor() as usize;
+        (0..=count).map(|i| (i as f64 * interval / (self.max - self.min)) as f32).collect()
+    }
+
+    fn event(&self, handle: usize) -> Event {
+        if self.float {
+            Event::FloatInserted(self.ids[handle], self.values[handle] as f32)
+        } else {
+            Event::NumberInserted(self.ids[handle], self.values[handle].round() as i32)
+        }
+    }
+}
+
+/// Pressing the mouse over the slider moves the nearest handle there, and drags it until
+/// the mouse is released, even outside the slider. While it has the focus, the arrows along its
+/// direction, PageUp, PageDown, Home and End move the last handle used, and Space chooses the next handle.
+/// Emits NumberInserted, or FloatInserted, with the ValueId of the handle moved.
+pub fn update_slider(widget: &mut Widget, data: &UpdateData) -> Option<Event> {
+    let track = match &widget.info {
+        WidgetInfo::Slider(slider) => Track::new(widget, slider.options.direction),
+        _ => return None,
+    };
+    let slider = match &mut widget.info {
+        WidgetInfo::Slider(slider) => slider,
+        _ => return None,
+    };
+    let (x, y) = data.mouse_position;
+    let inside = widget.space.is_inside(x, y);
+    let at = track.proportion(x, y);
+    let continuous = slider.options.emit == SliderEmit::Continuous;
+    let mut changed = false;
+    let mut emit = false;
+
+    match data.mouse_status {
+        MouseStatus::Pressed if inside => {
+            widget.status = WidgetStatus::Active;
+            // The nearest handle; between handles at the same place, the one that can move there
+            let distance = |i: &usize| (slider.proportion(*i) - at).abs();
+            let nearest = (0..slider.values.len())
+                .min_by(|a, b| distance(a).partial_cmp(&distance(b)).unwrap())
+                .unwrap_or(0);
+            let value = slider.values[nearest];
+            let mut same = (0..slider.values.len()).filter(|i| slider.values[*i] == value);
+            slider.handle = if at < slider.proportion(nearest) { same.next() } else { same.next_back() }.unwrap_or(nearest);
+            slider.dragging = true;
+            changed = slider.set_value(slider.handle, slider.min + at as f64 * (slider.max - slider.min));
+        },
+        MouseStatus::Released if slider.dragging => {
+            widget.status = if inside { WidgetStatus::Hover } else { WidgetStatus::Inactive };
+            slider.dragging = false;
+            emit = slider.pending;
+            slider.pending = false;
+        },
+        _ if slider.dragging => {
+            changed = slider.set_value(slider.handle, slider.min + at as f64 * (slider.max - slider.min));
+        },
+        _ => widget.status = if inside { WidgetStatus::Hover } else { WidgetStatus::Inactive },
+    }
+    if slider.dragging && !continuous {
+        slider.pending |= changed;
+    } else {
+        emit |= changed;
+    }
+
+    if widget.focused && !slider.dragging {
+        let handle = slider.handle;
+        let step = slider.key_step();
+        let value = slider.values[handle];
+        let target = match data.keyboard_status {
+            KeyboardStatus::KeyDown(Key::Space) => {
+                slider.handle = (handle + 1) % slider.values.len();
+                None
+            },
+            KeyboardStatus::KeyDown(Key::Right | Key::Up) => Some(value + step),
+            KeyboardStatus::KeyDown(Key::Left | Key::Down) => Some(value - step),
+            KeyboardStatus::KeyDown(Key::PageUp) => Some(value + step * PAGE_STEPS),
+            KeyboardStatus::KeyDown(Key::PageDown) => Some(value - step * PAGE_STEPS),
+            KeyboardStatus::KeyDown(Key::Home) => Some(slider.min),
+            KeyboardStatus::KeyDown(Key::End) => Some(slider.max),
+            _ => None,
+        };
+        if let Some(target) = target {
+            emit |= slider.set_value(handle, target);
+        }
+    }
+
+    if emit {
+        Some(slider.event(slider.handle))
+    } else {
+        None
+    }
+}
+
+/// Draws the track, the part chosen, the tick marks and the handles
+pub fn paint_slider(widget: &Widget, surface: &mut dyn Surface) {
+    let slider = match &widget.info {
+        WidgetInfo::Slider(slider) => slider,
+        _ => return,
+    };
+    let track = Track::new(widget, slider.options.direction);
+    if track.length == 0_f32 {
+        return;
+    }
+    let rect = |along1: f32, along2: f32, across1: f32, across2: f32| {
+        let (ax, ay) = track.point(along1, across1);
+        let (bx, by) = track.point(along2, across2);
+        let (x1, y1) = widget.space.to_prop(ax.min(bx), ay.min(by));
+        let (x2, y2) = widget.space.to_prop(ax.max(bx), ay.max(by));
+        (x1, y1, x2, y2)
+    };
+    let modern = matches!(widget.theme.style, WidgetStyle::Modern);
+    let half = TRACK * 0.5_f32;
+
+    // Track, and the part chosen: from the minimum to the value, or between the handles of a range
+    let (x1, y1, x2, y2) = rect(track.along(0_f32), track.along(1_f32), -half, half);
+    if modern {
+        surface.draw_rounded_rectangle(x1, y1, x2, y2, half as u32, None, Some(&widget.theme.active));
+    } else {
+        surface.draw_rectangle(x1, y1, x2, y2, Some(&widget.color_border), Some(&widget.theme.active));
+    }
+    let from = if slider.values.len() > 1 { slider.proportion(0) } else { 0_f32 };
+    let to = slider.proportion(slider.values.len() - 1);
+    let chosen = widget.state_color(widget.theme.focus);
+    let (x1, y1, x2, y2) = rect(track.along(from), track.along(to), -half, half);
+    surface.draw_rectangle(x1, y1, x2, y2, None, Some(&chosen));
+
+    // Tick marks, at one side of the track
+    for proportion in slider.ticks() {
+        let along = track.along(proportion);
+        let (x1, y1) = track.point(along, HANDLE * 0.5_f32);
+        let (x2, y2) = track.point(along, HANDLE * 0.5_f32 + TICK);
+        let (x1, y1) = widget.space.to_prop(x1, y1);
+        let (x2, y2) = widget.space.to_prop(x2, y2);
+        surface.draw_line(x1, y1, x2, y2, &widget.color_border);
+    }
+
+    // The handle moved by the keys is filled with the focus color, when there are several
+    let keyed = if widget.focused && slider.values.len() > 1 { Some(slider.handle) } else { None };
+    for handle in 0..slider.values.len() {
+        let fill = if keyed == Some(handle) { &widget.theme.focus } else { &widget.color_bg };
+        let along = track.along(slider.proportion(handle));
+        let half = HANDLE * 0.5_f32;
+        let (x1, y1, x2, y2) = rect(along - half, along + half, -half, half);
+        if modern {
+            surface.draw_circle((x1 + x2) * 0.5_f32, (y1 + y2) * 0.5_f32, (x2 - x1) * 0.5_f32,
+                Some(&widget.color_border), Some(fill));
+        } else {
+            surface.draw_rectangle(x1, y1, x2, y2, Some(&widget.color_border), Some(fill));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Space, Theme};
+
+    /// A focused range slider for the values 1 and 2, from 0 to 10, with the handles at 2 and 8
+    fn range_slider() -> Widget {
+        let mut widget = Widget::new_range_slider((1, 2), (2, 8), 0, 10, SliderOptions::default(), Theme::new());
+        widget.set_space(Space::new(200, 30));
+        widget.set_focus(true);
+        widget
+    }
+
+    fn press(widget: &mut Widget, key: Key) -> Option<Event> {
+        let mut data = UpdateData::new();
+        data.keyboard_status = KeyboardStatus::KeyDown(key);
+        widget.update(&data)
+    }
+
+    #[test]
+    fn keys_move_the_low_handle_first() {
+        let mut widget = range_slider();
+        assert_eq!(press(&mut widget, Key::End), Some(Event::NumberInserted(1, 8)));
+        assert_eq!(press(&mut widget, Key::Home), Some(Event::NumberInserted(1, 0)));
+    }
+
+    #[test]
+    fn space_chooses_the_next_handle() {
+        let mut widget = range_slider();
+        assert_eq!(press(&mut widget, Key::Space), None);
+        assert_eq!(press(&mut widget, Key::End), Some(Event::NumberInserted(2, 10)));
+        assert_eq!(press(&mut widget, Key::Home), Some(Event::NumberInserted(2, 2)));
+        assert_eq!(press(&mut widget, Key::Space), None);
+        assert_eq!(press(&mut widget, Key::Left), Some(Event::NumberInserted(1, 1)));
+    }
+
+    #[test]
+    fn ticks_need_a_range() {
+        let options = SliderOptions{ticks: Some(5_f32), ..SliderOptions::default()};
+        let slider = Slider::new(options, 0_f64, 10_f64, vec![0_f64], vec![1], false);
+        assert_eq!(slider.ticks(), vec![0_f32, 0.5_f32, 1_f32]);
+        let slider = Slider::new(options, 3_f64, 3_f64, vec![3_f64], vec![1], false);
+        assert!(slider.ticks().is_empty());
+    }
+}
@@ -0,0 +1,228 @@
+/*
+Copyright 2021 Gonzalo Fernández Hernández
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+use crate::{Surface, UpdateData, MouseStatus, KeyboardStatus, Key, Event, ValueId, ValueState, WidgetStyle};
+use crate::widget::{Widget, WidgetInfo, WidgetStatus};
+use crate::widget::text_input::{TextEdit, paint_edit, PADDING};
+
+/// Width in pixels of the column with the arrows
+const ARROWS_WIDTH: f32 = 18_f32;
+
+/// Pixels the mouse is moved up or down, while dragging, for every step
+const DRAG_PIXELS: f32 = 4_f32;
+
+/// Pixels the mouse must move, while pressed, to start dragging
+const DRAG_START: f32 = 3_f32;
+
+/// Steps changed with PageUp and PageDown
+const PAGE_STEPS: f64 = 10_f64;
+
+/// A number that can be typed or changed by steps
+///     * number: The number shown, always between min and max and rounded to the decimals
+///     * step: The change of the arrows, the wheel and the keys
+///     * decimals: Decimal places shown. Integers have none.
+///     * float: Whether the events are FloatInserted instead of NumberInserted
+///     * edit: The text typed
+///     * drag: Position y of the mouse and number when it was pressed over the text
+///     * dragging: Whether the mouse has moved enough to change the number
+pub struct SpinBox {
+    number: f64,
+    min: f64,
+    max: f64,
+    step: f64,
+    decimals: usize,
+    float: bool,
+    edit: TextEdit,
+    drag: Option<(u32, f64)>,
+    dragging: bool,
+}
+
+impl SpinBox {
+    pub fn new(number: f64, min: f64, max: f64, step: f64, decimals: usize, float: bool) -> Self {
+        let mut spin = SpinBox{
+            number, 
+            min: min.min(max), 
+            max: max.max(min), 
+            step, 
+            decimals, 
+            float,
+            edit: TextEdit::new(""),
+            drag: None,
+            dragging: false,
+        };
+        spin.set_number(number);
+        spin
+    }
+
+    /// Sets the number, limited to min and max and rounded to the decimals, and shows it
+    /// returns:
+    ///     Whether the number changed
+    pub fn set_number(&mut self, number: f64) -> bool {
+        let factor = 10_f64.powi(self.decimals as i32);
+        let number = (number.max(self.min).min(self.max) * factor).round() / factor;
+        let changed = number != self.number;
+        self.number = number;
+        self.edit.set_text(&format!("{:.*}", self.decimals, number));
+        changed
+    }
+
+    /// Adds some steps to the number
+    fn add_steps(&mut self, steps: f64) -> bool {
+        self.set_number(self.number + steps * self.step)
+    }
+
+    /// Sets the number typed. If the text is not a number, the last number is shown again.
+    fn commit_text(&mut self) -> bool {
+        self.edit.take_modified();
+        match self.edit.text().trim().replace(',', ".").parse::<f64>() {
+            Ok(number) => self.set_number(number),
+            Err(_) => self.set_number(self.number),
+        }
+    }
+
+    /// Whether the spin box uses a key itself: Up and Down change the number,
+    /// and Left and Right move the caret until it reaches an end of the text.
+    pub fn captures_key(&self, key: Key) -> bool {
+        matches!(key, Key::Up | Key::Down) || self.edit.moves_caret(key)
+    }
+
+    fn event(&self, value: ValueId) -> Event {
+        if self.float {
+            Event::FloatInserted(value, self.number as f32)
+        } else {
+            Event::NumberInserted(value, self.number as i32)
+        }
+    }
+}
+
+/// Characters that can be typed in a spin box
+fn accept(character: char) -> bool {
+    character.is_ascii_digit() || matches!(character, '-' | '+' | '.' | ',')
+}
+
+/// The arrows add or subtract a step, as Up and Down do; PageUp and PageDown change ten steps.
+/// The wheel changes a step per line while the mouse is over the spin box,
+/// and dragging the mouse up or down over the text changes a step every DRAG_PIXELS.
+/// A number typed is set when Enter is pressed or the spin box loses the focus.
+/// Every change emits NumberInserted, or FloatInserted.
+pub fn update_spin_box(widget: &mut Widget, data: &UpdateData) -> Option<Event> {
+    let spin = match &mut widget.info {
+        WidgetInfo::Spin(spin) => spin,
+        _ => return None,
+    };
+    let (x, y) = data.mouse_position;
+    let (px1, py1, px2, py2) = widget.space.pixel_coords;
+    let inside = widget.space.is_inside(x, y);
+    let on_arrows = inside && x as f32 >= px2 as f32 - ARROWS_WIDTH;
+    let lost_focus = spin.edit.update_focus(widget.focused);
+    let mut changed = false;
+
+    match data.mouse_status {
+        MouseStatus::Pressed if on_arrows => {
+            widget.status = WidgetStatus::Active;
+            changed |= spin.add_steps(if y < (py1 + py2) / 2 { 1_f64 } else { -1_f64 });
+        },
+        MouseStatus::Pressed if inside => {
+            widget.status = WidgetStatus::Active;
+            spin.drag = Some((y, spin.number));
+            spin.dragging = false;
+            spin.edit.move_to_point(x as f32 - px1 as f32 - PADDING, data.modifiers.shift);
+        },
+        MouseStatus::Released if widget.status == WidgetStatus::Active => {
+            widget.status = if inside { WidgetStatus::Hover } else { WidgetStatus::Inactive };
+            spin.drag = None;
+        },
+        _ if widget.status == WidgetStatus::Active => {
+            if let Some((start, number)) = spin.drag {
+                let moved = start as f32 - y as f32;
+                if spin.dragging || moved.abs() >= DRAG_START {
+                    spin.dragging = true;
+                    let steps = (moved / DRAG_PIXELS).trunc() as f64;
+                    changed |= spin.set_number(number + steps * spin.step);
+                }
+            }
+        },
+        _ => widget.status = if inside { WidgetStatus::Hover } else { WidgetStatus::Inactive },
+    }
+
+    if inside && data.wheel != 0_f32 {
+        // Touchpads scroll fractions of a line
+        let lines = if data.wheel.abs() < 1_f32 { data.wheel.signum() } else { data.wheel.round() };
+        changed |= spin.add_steps(lines as f64);
+    }
+
+    if widget.focused {
+        let steps = match data.keyboard_status {
+            KeyboardStatus::KeyDown(Key::Up) => Some(1_f64),
+            KeyboardStatus::KeyDown(Key::Down) => Some(-1_f64),
+            KeyboardStatus::KeyDown(Key::PageUp) => Some(PAGE_STEPS),
+            KeyboardStatus::KeyDown(Key::PageDown) => Some(-PAGE_STEPS),
+            _ => None,
+        };
+        match steps {
+            Some(steps) => changed |= spin.add_steps(steps),
+            None if spin.edit.process_keys(data, accept) => changed |= spin.commit_text(),
+            None => (),
+        }
+    } else if lost_focus && spin.edit.take_modified() {
+        changed |= spin.commit_text();
+    }
+
+    if changed {
+        widget.value.map(|value| spin.event(value))
+    } else {
+        None
+    }
+}
+
+/// Draws the box with the number, and the column with the arrows at its right
+pub fn paint_spin_box(widget: &Widget, surface: &mut dyn Surface) {
+    let spin = match &widget.info {
+        WidgetInfo::Spin(spin) => spin,
+        _ => return,
+    };
+    let (x1, y1, x2, y2) = widget.space.prop_coords;
+    let (px1, py1, px2, py2) = widget.space.pixel_coords;
+    let mark = widget.state_color(widget.theme.text);
+    let border = match widget.state {
+        ValueState::Correct | ValueState::Wrong => mark,
+        _ => widget.color_border,
+    };
+    match widget.theme.style {
+        WidgetStyle::Classic => surface.draw_rectangle(x1, y1, x2, y2, Some(&border), Some(&widget.theme.inactive)),
+        WidgetStyle::Modern => surface.draw_rounded_rectangle(x1, y1, x2, y2, PADDING as u32, Some(&border), Some(&widget.theme.inactive)),
+    }
+    let text_width = (px2 - px1) as f32 - ARROWS_WIDTH - 2_f32 * PADDING;
+    paint_edit(widget, &spin.edit, surface, text_width);
+
+    let left = px2 as f32 - ARROWS_WIDTH;
+    let middle = (py1 + py2) as f32 * 0.5_f32;
+    let (ax1, ay1) = widget.space.to_prop(left, py1 as f32);
+    let (_, ay2) = widget.space.to_prop(left, middle);
+    surface.draw_rectangle(ax1, ay1, x2, y2, Some(&border), Some(&widget.color_bg));
+    surface.draw_line(ax1, ay2, x2, ay2, &border);
+
+    // Triangles of half the width of the column, centered in each half
+    let size = (ARROWS_WIDTH * 0.25_f32).min((py2 - py1) as f32 * 0.25_f32);
+    let center = left + ARROWS_WIDTH * 0.5_f32;
+    for (sign, row) in [(-1_f32, (py1 as f32 + middle) * 0.5_f32), (1_f32, (middle + py2 as f32) * 0.5_f32)].iter() {
+        let (tx1, ty1) = widget.space.to_prop(center - size, row - sign * size * 0.5_f32);
+        let (tx2, ty2) = widget.space.to_prop(center + size, row - sign * size * 0.5_f32);
+        let (tx3, ty3) = widget.space.to_prop(center, row + sign * size * 0.5_f32);
+        surface.draw_triangle(tx1, ty1, tx2, ty2, tx3, ty3, None, Some(&mark));
+    }
+}
@@ -0,0 +1,431 @@
+/*
+Copyright 2021 Gonzalo Fernández Hernández
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+use std::cell::{Cell, RefCell};
+use std::ops::Range;
+use std::time::Instant;
+
+use crate::{Surface, UpdateData, MouseStatus, KeyboardStatus, Key, Event};
+use crate::{TextLayout, TextFormat, TextWrap, HorizontalAlign, VerticalAlign};
+use crate::widget::{Widget, WidgetInfo, WidgetStatus};
+
+/// Space in pixels between the border of the input and the text
+pub const PADDING: f32 = 4_f32;
+
+/// Time the caret is shown, and then hidden, while blinking
+const BLINK_MILLIS: u128 = 500;
+
+/// A single line of text being edited
+///     * caret: Byte index where the text is inserted
+///     * anchor: Byte index where the selection starts (the selection ends at caret)
+///     * modified: Whether the text has changed since it was last committed
+///     * focused: Whether the input had the focus in the last update, to commit when it is lost
+///     * scroll: Pixels the text is moved to the left to keep the caret visible
+///     * layout: The text as laid out in the last paint, used for hit testing
+pub struct TextEdit {
+    text: String,
+    caret: usize,
+    anchor: usize,
+    modified: bool,
+    focused: bool,
+    blink: Instant,
+    scroll: Cell<f32>,
+    layout: RefCell<Option<TextLayout>>,
+}
+
+impl TextEdit {
+    pub fn new(text: &str) -> Self {
+        TextEdit{
+            text: text.to_string(),
+            caret: text.len(),
+            anchor: text.len(),
+            modified: false,
+            focused: false,
+            blink: Instant::now(),
+            scroll: Cell::new(0_f32),
+            layout: RefCell::new(None),
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replaces the text, placing the caret at the end
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.caret = self.text.len();
+        self.anchor = self.caret;
+        self.modified = false;
+        *self.layout.get_mut() = None;
+    }
+
+    /// Bytes of the text selected
+    pub fn selection(&self) -> Range<usize> {
+        self.caret.min(self.anchor) .. self.caret.max(self.anchor)
+    }
+
+    /// Moves the caret. If select is false, the selection is removed.
+    pub fn move_to(&mut self, position: usize, select: bool) {
+        self.caret = position.min(self.text.len());
+        if !select {
+            self.anchor = self.caret;
+        }
+        self.blink = Instant::now();
+    }
+
+    /// Replaces the selection with a text
+    pub fn insert(&mut self, text: &str) {
+        let selection = self.selection();
+        self.text.replace_range(selection.clone(), text);
+        self.move_to(selection.start + text.len(), false);
+        self.modified = true;
+    }
+
+    /// Deletes the selection or, if there is none, from the caret to a position
+    pub fn delete_to(&mut self, position: usize) {
+        if self.caret == self.anchor {
+            if position == self.caret {
+                // Nothing before the start or after the end, the text does not change
+                return;
+            }
+            self.anchor = position;
+        }
+        self.insert("");
+    }
+
+    /// Position of the character before a position
+    pub fn prev_char(&self, position: usize) -> usize {
+        self.text[..position].char_indices().last().map(|x| x.0).unwrap_or(0)
+    }
+
+    /// Position of the character after a position
+    pub fn next_char(&self, position: usize) -> usize {
+        self.text[position..].chars().next().map(|x| position + x.len_utf8()).unwrap_or(position)
+    }
+
+    /// Position of the start of the word before a position
+    pub fn prev_word(&self, position: usize) -> usize {
+        let text = self.text[..position].trim_end();
+        text.rfind(' ').map(|x| x + 1).unwrap_or(0)
+    }
+
+    /// Position of the start of the word after a position
+    pub fn next_word(&self, position: usize) -> usize {
+        let rest = &self.text[position..];
+        let word_end = rest.find(' ').unwrap_or(rest.len());
+        let spaces = rest[word_end..].len() - rest[word_end..].trim_start().len();
+        position + word_end + spaces
+    }
+
+    /// Whether Left or Right can still move the caret, or remove the selection
+    pub fn moves_caret(&self, key: Key) -> bool {
+        match key {
+            Key::Left => self.caret > 0 || self.caret != self.anchor,
+            Key::Right => self.caret < self.text.len() || self.caret != self.anchor,
+            _ => false,
+        }
+    }
+
+    /// Whether the caret is drawn at this moment
+    fn caret_visible(&self) -> bool {
+        (self.blink.elapsed().as_millis() / BLINK_MILLIS).is_multiple_of(2)
+    }
+
+    /// Byte index nearest to a position in pixels from the start of the text box
+    fn hit_test(&self, x: f32) -> usize {
+        match &*self.layout.borrow() {
+            Some(layout) => layout.hit_test(x + self.scroll.get(), 0_f32).min(self.text.len()),
+            None => self.text.len(),
+        }
+    }
+
+    /// Moves the caret to a position in pixels from the start of the text box, as painted last time
+    pub fn move_to_point(&mut self, x: f32, select: bool) {
+        self.move_to(self.hit_test(x), select);
+    }
+
+    /// Keeps whether the widget has the focus
+    /// returns:
+    ///     Whether it has just lost it
+    pub fn update_focus(&mut self, focused: bool) -> bool {
+        let lost = self.focused && !focused;
+        self.focused = focused;
+        lost
+    }
+
+    /// Returns whether the text has changed since the last call
+    pub fn take_modified(&mut self) -> bool {
+        std::mem::replace(&mut self.modified, false)
+    }
+
+    /// Edits the text with the keys pressed and the characters typed in an update
+    /// params:
+    ///     * data: The input of the update
+    ///     * accept: Whether a character typed can be inserted
+    /// returns:
+    ///     Whether Enter was pressed
+    pub fn process_keys(&mut self, data: &UpdateData, accept: fn(char) -> bool) -> bool {
+        let select = data.modifiers.shift;
+        let word = data.modifiers.ctrl;
+        let mut enter = false;
+        if let KeyboardStatus::KeyDown(key) = data.keyboard_status {
+            let caret = self.caret;
+            let selection = self.selection();
+            match key {
+                Key::Left if !select && !selection.is_empty() => self.move_to(selection.start, false),
+                Key::Right if !select && !selection.is_empty() => self.move_to(selection.end, false),
+                Key::Left if word => self.move_to(self.prev_word(caret), select),
+                Key::Left => self.move_to(self.prev_char(caret), select),
+                Key::Right if word => self.move_to(self.next_word(caret), select),
+                Key::Right => self.move_to(self.next_char(caret), select),
+                Key::Home => self.move_to(0, select),
+                Key::End => self.move_to(self.text.len(), select),
+                Key::Backspace if word => self.delete_to(self.prev_word(caret)),
+                Key::Backspace => self.delete_to(self.prev_char(caret)),
+                Key::Delete if word => self.delete_to(self.next_word(caret)),
+                Key::Delete => self.delete_to(self.next_char(caret)),
+                Key::Enter => enter = true,
+                _ => (),
+            }
+        }
+        let typed: String = data.text.chars().filter(|x| accept(*x)).collect();
+        if !typed.is_empty() {
+            self.insert(&typed);
+        }
+        enter
+    }
+}
+
+/// Clicking the input places the caret; dragging selects.
+/// While focused, the keys edit the text. The text is committed with TextInserted
+/// when Enter is pressed or the input loses the focus.
+pub fn update_text_input(widget: &mut Widget, data: &UpdateData) -> Option<Event> {
+    let edit = match &mut widget.info {
+        WidgetInfo::Edit(edit) => edit,
+        _ => return None,
+    };
+    let (x, y) = data.mouse_position;
+    let inside = widget.space.is_inside(x, y);
+    let text_x = x as f32 - widget.space.pixel_coords.0 as f32 - PADDING;
+    let lost_focus = edit.update_focus(widget.focused);
+
+    match data.mouse_status {
+        MouseStatus::Pressed if inside => {
+            widget.status = WidgetStatus::Active;
+            edit.move_to_point(text_x, data.modifiers.shift);
+        },
+        MouseStatus::Released if widget.status == WidgetStatus::Active => {
+            widget.status = if inside { WidgetStatus::Hover } else { WidgetStatus::Inactive };
+        },
+        _ if widget.status == WidgetStatus::Active => edit.move_to_point(text_x, true),
+        _ => widget.status = if inside { WidgetStatus::Hover } else { WidgetStatus::Inactive },
+    }
+
+    let commit = if widget.focused { edit.process_keys(data, |_| true) } else { lost_focus };
+    if commit && edit.take_modified() {
+        return widget.value.map(|value| Event::TextInserted(value, edit.text.clone()));
+    }
+    None
+}
+
+/// Draws the box, the selection, the visible part of the text and the caret
+pub fn paint_text_input(widget: &Widget, surface: &mut dyn Surface) {
+    let edit = match &widget.info {
+        WidgetInfo::Edit(edit) => edit,
+        _ => return,
+    };
+    let (x1, y1, x2, y2) = widget.space.prop_coords;
+    let (px1, _, px2, _) = widget.space.pixel_coords;
+    surface.draw_rectangle(x1, y1, x2, y2, Some(&widget.color_border), Some(&widget.theme.inactive));
+    paint_edit(widget, edit, surface, (px2 - px1) as f32 - 2_f32 * PADDING);
+}
+
+/// Draws the selection, the visible part of the text and the caret of a TextEdit.
+/// The text box starts PADDING pixels at the right of the left side of the widget, and fills its height.
+/// params:
+///     * widget: The widget that contains the TextEdit
+///     * edit: The text and caret
+///     * surface: The surface to draw into
+///     * width: The width in pixels of the text box
+pub fn paint_edit(widget: &Widget, edit: &TextEdit, surface: &mut dyn Surface, width: f32) {
+    let (x1, y1, _, y2) = widget.space.prop_coords;
+    let (px1, py1, px2, py2) = widget.space.pixel_coords;
+    let height = (py2 - py1) as f32;
+    if width <= 0_f32 || height <= 0_f32 {
+        return;
+    }
+    let format = TextFormat{
+        wrap: TextWrap::NoWrap,
+        horizontal: HorizontalAlign::Left,
+        vertical: VerticalAlign::Middle,
+        ellipsis: false,
+        ..widget.theme.text_format
+    };
+    let layout = TextLayout::new(&edit.text, width, height, &format, surface);
+    let line = match layout.lines.first() {
+        Some(line) => line,
+        None => {
+            *edit.layout.borrow_mut() = None;
+            return;
+        },
+    };
+
+    let caret_x = layout.caret_position(edit.caret).0;
+    let mut scroll = edit.scroll.get().min((line.width - width).max(0_f32));
+    if caret_x - scroll > width {
+        scroll = caret_x - width;
+    } else if caret_x < scroll {
+        scroll = caret_x;
+    }
+    edit.scroll.set(scroll);
+
+    let scale_x = (widget.space.prop_coords.2 - x1) / (px2 - px1) as f32;
+    let scale_y = (y2 - y1) / height;
+    let to_x = |px: f32| x1 + (PADDING + (px - scroll).clamp(0_f32, width)) * scale_x;
+    let (top, bottom) = (y1 + line.y * scale_y, y1 + (line.y + layout.line_height) * scale_y);
+
+    let selection = edit.selection();
+    if !selection.is_empty() {
+        let start = layout.caret_position(selection.start).0;
+        let end = layout.caret_position(selection.end).0;
+        surface.draw_rectangle(to_x(start), top, to_x(end), bottom, None, Some(&widget.theme.hover));
+    }
+
+    if let Some(first) = line.glyphs.iter().find(|glyph| glyph.x >= scroll) {
+        surface.draw_text(&edit.text[first.index..], to_x(first.x), top, to_x(scroll + width), y2,
+            &widget.text_color(), &format.style);
+    }
+
+    if widget.focused && edit.caret_visible() {
+        surface.draw_line(to_x(caret_x), top, to_x(caret_x), bottom, &widget.theme.text);
+    }
+    *edit.layout.borrow_mut() = Some(layout);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Modifiers;
+
+    /// "a", "é" (2 bytes), "€" (3 bytes) and "b"
+    const MULTI_BYTE: &str = "a\u{e9}\u{20ac}b";
+
+    fn key(edit: &mut TextEdit, key: Key, shift: bool, ctrl: bool) -> bool {
+        let mut data = UpdateData::new();
+        data.keyboard_status = KeyboardStatus::KeyDown(key);
+        data.modifiers = Modifiers{shift, ctrl, ..Modifiers::default()};
+        edit.process_keys(&data, |_| true)
+    }
+
+    #[test]
+    fn characters_are_stepped_on_their_boundaries() {
+        let edit = TextEdit::new(MULTI_BYTE);
+        assert_eq!(edit.prev_char(7), 6);
+        assert_eq!(edit.prev_char(6), 3);
+        assert_eq!(edit.prev_char(3), 1);
+        assert_eq!(edit.prev_char(0), 0);
+        assert_eq!(edit.next_char(1), 3);
+        assert_eq!(edit.next_char(3), 6);
+        assert_eq!(edit.next_char(7), 7);
+    }
+
+    #[test]
+    fn words_start_after_the_spaces() {
+        let edit = TextEdit::new("one  two three");
+        assert_eq!(edit.prev_word(14), 9);
+        assert_eq!(edit.prev_word(9), 5);
+        assert_eq!(edit.prev_word(5), 0);
+        assert_eq!(edit.next_word(0), 5);
+        assert_eq!(edit.next_word(5), 9);
+        assert_eq!(edit.next_word(9), 14);
+
+        let edit = TextEdit::new("d\u{ed}a m\u{e1}s");
+        assert_eq!(edit.prev_word(edit.text().len()), 5);
+        assert_eq!(edit.next_word(0), 5);
+    }
+
+    #[test]
+    fn selection_is_replaced_by_the_text_inserted() {
+        let mut edit = TextEdit::new(MULTI_BYTE);
+        edit.move_to(1, false);
+        edit.move_to(6, true);
+        assert_eq!(edit.selection(), 1..6);
+        edit.insert("x");
+        assert_eq!(edit.text(), "axb");
+        assert_eq!(edit.selection(), 2..2);
+        assert!(edit.take_modified());
+        assert!(!edit.take_modified());
+    }
+
+    #[test]
+    fn delete_removes_the_selection_or_up_to_a_position() {
+        let mut edit = TextEdit::new(MULTI_BYTE);
+        edit.delete_to(edit.prev_char(7));
+        edit.delete_to(edit.prev_char(6));
+        assert_eq!(edit.text(), "a\u{e9}");
+        assert!(edit.take_modified());
+
+        // Nothing before the start, so nothing changes
+        edit.move_to(0, false);
+        edit.delete_to(edit.prev_char(0));
+        assert!(!edit.take_modified());
+
+        // A selection is deleted, whatever the position
+        edit.move_to(3, true);
+        edit.delete_to(0);
+        assert_eq!(edit.text(), "");
+    }
+
+    #[test]
+    fn keys_move_select_and_edit() {
+        let mut edit = TextEdit::new("one two");
+        key(&mut edit, Key::Left, true, true);
+        assert_eq!(edit.selection(), 4..7);
+        // Left without shift goes to the start of the selection
+        key(&mut edit, Key::Left, false, false);
+        assert_eq!(edit.selection(), 4..4);
+        key(&mut edit, Key::Backspace, false, true);
+        assert_eq!(edit.text(), "two");
+        key(&mut edit, Key::End, true, false);
+        key(&mut edit, Key::Delete, false, false);
+        assert_eq!(edit.text(), "");
+        assert!(key(&mut edit, Key::Enter, false, false));
+        assert!(!key(&mut edit, Key::Home, false, false));
+    }
+
+    #[test]
+    fn characters_typed_are_filtered() {
+        let mut edit = TextEdit::new("");
+        let mut data = UpdateData::new();
+        data.text = "1a\u{e9}2".to_string();
+        edit.process_keys(&data, |x| x.is_ascii_digit());
+        assert_eq!(edit.text(), "12");
+        assert_eq!(edit.selection(), 2..2);
+    }
+
+    #[test]
+    fn arrows_are_kept_while_the_caret_can_move() {
+        let mut edit = TextEdit::new("ab");
+        assert!(edit.moves_caret(Key::Left));
+        assert!(!edit.moves_caret(Key::Right));
+        edit.move_to(0, true);
+        assert!(edit.moves_caret(Key::Right));
+        edit.move_to(0, false);
+        assert!(!edit.moves_caret(Key::Left));
+    }
+}
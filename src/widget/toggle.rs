@@ -0,0 +1,61 @@
+/*
+Copyright 2021 Gonzalo Fernández Hernández
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+
+use crate::{Surface, WidgetStyle};
+use crate::widget::{Widget, WidgetInfo};
+use crate::widget::checkbox::{paint_caption, PADDING, GAP};
+
+/// Maximum height in pixels of the track
+const TRACK_HEIGHT: f32 = 20_f32;
+
+/// Space in pixels between the track and the knob
+const KNOB_MARGIN: f32 = 3_f32;
+
+/// Draws a track with a knob at the right when on and at the left when off, and the caption at its right.
+/// The input is handled like a checkbox.
+pub fn paint_toggle(widget: &Widget, surface: &mut dyn Surface) {
+    let on = matches!(widget.info, WidgetInfo::Check(true, _));
+    let (px1, py1, _, py2) = widget.space.pixel_coords;
+    let height = ((py2 - py1) as f32 - 2_f32 * PADDING).min(TRACK_HEIGHT);
+    if height <= 2_f32 * KNOB_MARGIN {
+        return;
+    }
+    let width = height * 2_f32;
+    let left = px1 as f32 + PADDING;
+    let top = (py1 + py2) as f32 * 0.5_f32 - height * 0.5_f32;
+    let (x1, y1) = widget.space.to_prop(left, top);
+    let (x2, y2) = widget.space.to_prop(left + width, top + height);
+
+    let track = if on { widget.state_color(widget.theme.focus) } else { widget.theme.active };
+    let knob = height - 2_f32 * KNOB_MARGIN;
+    let knob_left = if on { left + width - KNOB_MARGIN - knob } else { left + KNOB_MARGIN };
+    let (kx1, ky1) = widget.space.to_prop(knob_left, top + KNOB_MARGIN);
+    let (kx2, ky2) = widget.space.to_prop(knob_left + knob, top + KNOB_MARGIN + knob);
+
+    match widget.theme.style {
+        WidgetStyle::Classic => {
+            surface.draw_rectangle(x1, y1, x2, y2, Some(&widget.color_border), Some(&track));
+            surface.draw_rectangle(kx1, ky1, kx2, ky2, Some(&widget.color_border), Some(&widget.color_bg));
+        },
+        WidgetStyle::Modern => {
+            surface.draw_rounded_rectangle(x1, y1, x2, y2, (height * 0.5_f32) as u32, Some(&track), Some(&track));
+            surface.draw_circle((kx1 + kx2) * 0.5_f32, (ky1 + ky2) * 0.5_f32, (kx2 - kx1) * 0.5_f32,
+                Some(&widget.color_border), Some(&widget.color_bg));
+        },
+    }
+    paint_caption(widget, surface, left + width + GAP);
+}